@@ -0,0 +1,186 @@
+use crate::{
+    contract::{hex_encode, MAX_ANIMATION_FRAMES},
+    glyphs::{
+        glyph_bundle_record, glyph_bundle_remove, glyph_escrow, glyph_escrow_release, glyph_status,
+        glyph_verify_ownership,
+    },
+    storage::persistent::{read_animation, read_glyph, remove_animation, write_animation},
+    types::{Error, GlyphStatus},
+};
+use soroban_sdk::{Address, Bytes, BytesN, Env, Vec};
+
+// Bundle glyphs `minter` owns into an animation, frames are (hash, delay in ms) and may repeat
+// The frames are held by the contract for as long as the animation exists
+pub fn glyph_animate(
+    env: &Env,
+    minter: Address,
+    to: Option<Address>,
+    frames: Vec<(BytesN<32>, u32)>,
+) -> Result<BytesN<32>, Error> {
+    if frames.len() < 2 || frames.len() > MAX_ANIMATION_FRAMES {
+        return Err(Error::NotPermitted);
+    }
+
+    let mut frame_hashes = Bytes::new(env);
+    let mut escrowed: Vec<BytesN<32>> = Vec::new(env);
+
+    for (frame_hash, delay) in frames.iter() {
+        if delay == 0 {
+            return Err(Error::NotPermitted);
+        }
+
+        frame_hashes.append(&frame_hash.clone().into());
+
+        if !escrowed.contains(frame_hash.clone()) {
+            escrowed.push_back(frame_hash);
+        }
+    }
+
+    let hash = env.crypto().sha256(&frame_hashes).to_bytes();
+
+    if glyph_status(env, &hash) != GlyphStatus::Unknown {
+        return Err(Error::NotEmpty);
+    }
+
+    glyph_escrow(env, &minter, &escrowed)?;
+
+    let owner = to.unwrap_or(minter.clone());
+
+    write_animation(env, &hash, &frames);
+    glyph_bundle_record(env, &minter, &owner, &hash);
+
+    Ok(hash)
+}
+
+// Break an animation back up, handing its frames to the animation's owner
+pub fn glyph_unanimate(env: &Env, hash: &BytesN<32>) -> Result<(), Error> {
+    let frames = read_animation(env, hash).ok_or(Error::NotFound)?;
+    let owner = glyph_verify_ownership(env, hash, None);
+    let mut frame_hashes: Vec<BytesN<32>> = Vec::new(env);
+
+    for (frame_hash, _) in frames.iter() {
+        frame_hashes.push_back(frame_hash);
+    }
+
+    glyph_escrow_release(env, &frame_hashes, &owner);
+    glyph_bundle_remove(env, &owner, hash);
+    remove_animation(env, hash);
+
+    Ok(())
+}
+
+// Render the animation as an SVG, one pixel per unit, with each frame shown in turn on a loop
+pub fn glyph_animation_svg(env: &Env, hash: &BytesN<32>) -> Result<Bytes, Error> {
+    let frames = read_animation(env, hash).ok_or(Error::NotFound)?;
+
+    let mut width: u32 = 0;
+    let mut height: u32 = 0;
+    let mut duration: u32 = 0;
+
+    for (frame_hash, delay) in frames.iter() {
+        let glyph = read_glyph(env, &frame_hash).ok_or(Error::NotFound)?;
+
+        width = width.max(glyph.width);
+        height = height.max(glyph.length.div_ceil(glyph.width));
+        duration += delay;
+    }
+
+    let mut svg = Bytes::new(env);
+
+    svg.extend_from_slice(b"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 ");
+    push_u32(&mut svg, width);
+    svg.push_back(b' ');
+    push_u32(&mut svg, height);
+    svg.extend_from_slice(b"\" shape-rendering=\"crispEdges\">");
+
+    let mut start: u32 = 0;
+
+    for (frame_hash, delay) in frames.iter() {
+        let glyph = read_glyph(env, &frame_hash).ok_or(Error::NotFound)?;
+        let end = start + delay;
+
+        svg.extend_from_slice(b"<g visibility=\"hidden\">");
+
+        for (_, color_indexes) in glyph.colors.iter() {
+            for (color, indexes) in color_indexes.iter() {
+                for index in indexes.iter() {
+                    svg.extend_from_slice(b"<rect x=\"");
+                    push_u32(&mut svg, index % glyph.width);
+                    svg.extend_from_slice(b"\" y=\"");
+                    push_u32(&mut svg, index / glyph.width);
+                    svg.extend_from_slice(b"\" width=\"1\" height=\"1\" fill=\"#");
+                    push_hex_color(&mut svg, color);
+                    svg.extend_from_slice(b"\"/>");
+                }
+            }
+        }
+
+        // Discrete keyTimes must open at 0 so frames other than the first start out hidden
+        svg.extend_from_slice(b"<animate attributeName=\"visibility\" values=\"");
+
+        if start == 0 {
+            svg.extend_from_slice(b"visible;hidden\" keyTimes=\"0;");
+            push_fraction(&mut svg, end, duration);
+        } else {
+            svg.extend_from_slice(b"hidden;visible;hidden\" keyTimes=\"0;");
+            push_fraction(&mut svg, start, duration);
+            svg.push_back(b';');
+            push_fraction(&mut svg, end, duration);
+        }
+
+        svg.extend_from_slice(b"\" dur=\"");
+        push_u32(&mut svg, duration);
+        svg.extend_from_slice(b"ms\" calcMode=\"discrete\" repeatCount=\"indefinite\"/></g>");
+
+        start = end;
+    }
+
+    svg.extend_from_slice(b"</svg>");
+
+    Ok(svg)
+}
+
+fn push_u32(bytes: &mut Bytes, value: u32) {
+    let mut digits = [0u8; 10];
+    let mut i = digits.len();
+    let mut value = value;
+
+    loop {
+        i -= 1;
+        digits[i] = b'0' + (value % 10) as u8;
+        value /= 10;
+
+        if value == 0 {
+            break;
+        }
+    }
+
+    bytes.extend_from_slice(&digits[i..]);
+}
+
+fn push_hex_color(bytes: &mut Bytes, color: u32) {
+    let mut hex = [0u8; 6];
+
+    hex_encode(&color.to_be_bytes()[1..], &mut hex);
+    bytes.extend_from_slice(&hex);
+}
+
+// `numerator / denominator` to four decimal places, which is as fine as keyTimes need to be
+fn push_fraction(bytes: &mut Bytes, numerator: u32, denominator: u32) {
+    if numerator >= denominator {
+        bytes.push_back(b'1');
+        return;
+    }
+
+    let ten_thousandths = (numerator as u64 * 10_000 / denominator as u64) as u32;
+    let mut digits = [b'0'; 4];
+    let mut value = ten_thousandths;
+
+    for i in (0..4).rev() {
+        digits[i] = b'0' + (value % 10) as u8;
+        value /= 10;
+    }
+
+    bytes.extend_from_slice(b"0.");
+    bytes.extend_from_slice(&digits);
+}
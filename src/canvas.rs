@@ -0,0 +1,206 @@
+use crate::{
+    contract::MAX_BIT24_SIZE,
+    glyphs::{glyph_hash, glyph_mint_record, glyph_require_reserver, glyph_status, glyph_store},
+    storage::{
+        instance::{read_canvas_count, write_canvas_count},
+        persistent::{
+            read_canvas, read_canvas_chunk, read_color, write_canvas, write_canvas_chunk,
+            write_canvas_snapshot, write_color, write_color_locked, write_color_unlocked,
+            write_glyph_contributors,
+        },
+        CANVAS_CHUNK_SIZE,
+    },
+    types::{Canvas, CanvasPixel, Error, GlyphStatus},
+};
+use soroban_sdk::{Address, BytesN, Env, Map, Vec};
+
+/* TODO
+Paint events for indexers
+Let the canvas owner cap how many pixels a single painter can hold
+*/
+
+pub fn canvas_create(
+    env: &Env,
+    owner: Address,
+    width: u32,
+    height: u32,
+    burn: bool,
+) -> Result<u32, Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::MissingWidth);
+    }
+
+    // A canvas must always be able to freeze into a glyph
+    if width > u8::MAX as u32 || width as usize * height as usize * 3 >= MAX_BIT24_SIZE {
+        return Err(Error::NotPermitted);
+    }
+
+    let id = read_canvas_count(env);
+
+    write_canvas(
+        env,
+        id,
+        &Canvas {
+            owner,
+            width,
+            height,
+            burn,
+        },
+    );
+    write_canvas_count(env, &(id + 1));
+
+    Ok(id)
+}
+
+// Paint `painter`'s colors onto the canvas, pixels use the same (miner, color, indexes) layout as a glyph build
+// Whatever was underneath goes back to its painter unless the canvas burns painted over colors
+pub fn canvas_paint(
+    env: &Env,
+    id: u32,
+    painter: Address,
+    colors: Map<Address, Map<u32, Vec<u32>>>,
+) -> Result<(), Error> {
+    let canvas = read_canvas(env, id).ok_or(Error::NotFound)?;
+    let size = canvas.width * canvas.height;
+    let mut chunks: Map<u32, Map<u32, CanvasPixel>> = Map::new(env);
+
+    for (miner, color_indexes) in colors.iter() {
+        for (color, indexes) in color_indexes.iter() {
+            let current_color_amount = read_color(env, &miner, &painter, color);
+
+            if indexes.len() > current_color_amount {
+                return Err(Error::NotPermitted);
+            }
+
+            write_color(
+                env,
+                &miner,
+                &painter,
+                color,
+                current_color_amount - indexes.len(),
+            );
+            write_color_locked(env, color, indexes.len());
+
+            for index in indexes.iter() {
+                if index >= size {
+                    return Err(Error::NotPermitted);
+                }
+
+                let chunk = index / CANVAS_CHUNK_SIZE;
+                let mut pixels = chunks
+                    .get(chunk)
+                    .unwrap_or_else(|| read_canvas_chunk(env, id, chunk));
+
+                if let Some(previous) = pixels.get(index) {
+                    write_color_unlocked(env, previous.color, 1, canvas.burn);
+
+                    if !canvas.burn {
+                        let previous_color_amount =
+                            read_color(env, &previous.miner, &previous.painter, previous.color);
+
+                        write_color(
+                            env,
+                            &previous.miner,
+                            &previous.painter,
+                            previous.color,
+                            previous_color_amount + 1,
+                        );
+                    }
+                }
+
+                pixels.set(
+                    index,
+                    CanvasPixel {
+                        painter: painter.clone(),
+                        miner: miner.clone(),
+                        color,
+                    },
+                );
+                chunks.set(chunk, pixels);
+            }
+        }
+    }
+
+    for (chunk, pixels) in chunks.iter() {
+        write_canvas_chunk(env, id, chunk, &pixels);
+    }
+
+    Ok(())
+}
+
+pub fn canvas_pixels(
+    env: &Env,
+    id: u32,
+    cursor: u32,
+    limit: u32,
+) -> Result<Vec<(u32, CanvasPixel)>, Error> {
+    let canvas = read_canvas(env, id).ok_or(Error::NotFound)?;
+    let end = (canvas.width * canvas.height).min(cursor.saturating_add(limit));
+    let mut pixels = Vec::new(env);
+
+    if cursor >= end {
+        return Ok(pixels);
+    }
+
+    for chunk in cursor / CANVAS_CHUNK_SIZE..=(end - 1) / CANVAS_CHUNK_SIZE {
+        for (index, pixel) in read_canvas_chunk(env, id, chunk).iter() {
+            if index >= cursor && index < end {
+                pixels.push_back((index, pixel));
+            }
+        }
+    }
+
+    Ok(pixels)
+}
+
+// Snapshot the canvas into a regular glyph owned by `to` (or the canvas owner), painters are kept on as glyph contributors
+// The painted colors stay locked in the canvas which carries on being painted, so the snapshot can't be scraped or repainted
+pub fn canvas_freeze(env: &Env, id: u32, to: Option<Address>) -> Result<BytesN<32>, Error> {
+    let canvas = read_canvas(env, id).ok_or(Error::NotFound)?;
+
+    canvas.owner.require_auth();
+
+    let mut colors: Map<Address, Map<u32, Vec<u32>>> = Map::new(env);
+    let mut contributors: Map<Address, u32> = Map::new(env);
+
+    for chunk in 0..(canvas.width * canvas.height).div_ceil(CANVAS_CHUNK_SIZE) {
+        for (index, pixel) in read_canvas_chunk(env, id, chunk).iter() {
+            let mut color_indexes = colors.get(pixel.miner.clone()).unwrap_or(Map::new(env));
+            let mut indexes = color_indexes.get(pixel.color).unwrap_or(Vec::new(env));
+
+            indexes.push_back(index);
+            color_indexes.set(pixel.color, indexes);
+            colors.set(pixel.miner, color_indexes);
+
+            if pixel.painter != canvas.owner {
+                contributors.set(
+                    pixel.painter.clone(),
+                    contributors.get(pixel.painter).unwrap_or(0) + 1,
+                );
+            }
+        }
+    }
+
+    if colors.is_empty() {
+        return Err(Error::NotPermitted);
+    }
+
+    let (hash, _) = glyph_hash(env, &colors, canvas.width as u8);
+
+    match glyph_status(env, &hash) {
+        GlyphStatus::Unknown | GlyphStatus::Scraped => {}
+        _ => return Err(Error::NotEmpty),
+    }
+
+    glyph_require_reserver(env, &hash, &canvas.owner)?;
+
+    let owner = to.unwrap_or(canvas.owner.clone());
+
+    glyph_store(env, colors, canvas.width as u8);
+    glyph_mint_record(env, &canvas.owner, &owner, &hash);
+
+    write_glyph_contributors(env, &hash, &contributors);
+    write_canvas_snapshot(env, &hash, id);
+
+    Ok(hash)
+}
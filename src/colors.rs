@@ -0,0 +1,187 @@
+use core::ops::Mul;
+
+use crate::{
+    storage::{
+        instance::{read_fee_address, read_mine_multiplier, read_mine_pricing, read_token_address},
+        persistent::{
+            read_color, read_color_allowance, read_color_operator, read_color_supply, write_color,
+            write_color_allowance, write_color_mined,
+        },
+    },
+    types::{Error, MinePricing, PriceCurve},
+};
+use soroban_sdk::{panic_with_error, token, Address, Env, Map, Vec};
+
+// Credit `to` with freshly mined `colors` under `miner`, `source` pays the current mining price
+// Returns the amount charged, which must not run past `max_cost` when one is given
+pub fn colors_mine(
+    env: &Env,
+    source: &Address,
+    miner: &Address,
+    to: &Address,
+    colors: &Map<u32, u32>,
+    max_cost: Option<i128>,
+) -> Result<i128, Error> {
+    let charged = colors_mine_cost(env, colors);
+
+    if max_cost.is_some_and(|max_cost| charged > max_cost) {
+        return Err(Error::MaxCostExceeded);
+    }
+
+    for (color, amount) in colors.iter() {
+        write_color_mined(env, color, amount);
+
+        let current_amount = read_color(env, miner, to, color);
+        write_color(env, miner, to, color, current_amount + amount);
+    }
+
+    // crate::events::colors_mine(env, miner, to, colors);
+
+    let token_address = read_token_address(env);
+    let fee_address = read_fee_address(env);
+
+    let token = token::Client::new(env, &token_address);
+
+    token.transfer(source, &fee_address, &charged);
+
+    Ok(charged)
+}
+
+// What mining `colors` would cost right now
+pub fn colors_mine_cost(env: &Env, colors: &Map<u32, u32>) -> i128 {
+    let mine_multiplier = read_mine_multiplier(env);
+    let mine_pricing = read_mine_pricing(env);
+    let mut cost: i128 = 0;
+
+    for (color, amount) in colors.iter() {
+        cost += match &mine_pricing {
+            Some(mine_pricing) => {
+                let mined = read_color_supply(env, color).mined;
+
+                curve_cost(mine_pricing, mine_multiplier, mined, mined + amount as u64)
+            }
+            None => (amount as i128).mul(mine_multiplier),
+        };
+    }
+
+    cost
+}
+
+pub fn mine_pricing_verify(mine_pricing: &MinePricing) -> Result<(), Error> {
+    let rate_ok = match mine_pricing.curve {
+        PriceCurve::Linear => mine_pricing.rate >= 0,
+        PriceCurve::Exponential => mine_pricing.rate > 0,
+    };
+
+    if !rate_ok || mine_pricing.floor < 0 || mine_pricing.cap < mine_pricing.floor {
+        return Err(Error::NotPermitted);
+    }
+
+    Ok(())
+}
+
+// Sum of the per pixel prices from the `start`th to the `end`th pixel of a color ever mined
+// Both curves only ever rise so the floor and cap each cover one end of the range
+fn curve_cost(mine_pricing: &MinePricing, base: i128, start: u64, end: u64) -> i128 {
+    let MinePricing {
+        curve,
+        rate,
+        floor,
+        cap,
+    } = *mine_pricing;
+
+    match curve {
+        PriceCurve::Linear => {
+            // First pixel priced at or above `price`
+            let reach = |price: i128| -> u64 {
+                if price <= base {
+                    0
+                } else if rate == 0 {
+                    u64::MAX
+                } else {
+                    u64::try_from((price - base + rate - 1) / rate).unwrap_or(u64::MAX)
+                }
+            };
+
+            let floor_end = reach(floor).clamp(start, end);
+            let cap_start = reach(cap).clamp(floor_end, end);
+            let count = (cap_start - floor_end) as i128;
+
+            // (count) and (first + last) never share parity so the halving is exact
+            let ramp =
+                base * count + rate * (count * (floor_end as i128 + cap_start as i128 - 1) / 2);
+
+            floor * (floor_end - start) as i128 + ramp + cap * (end - cap_start) as i128
+        }
+        PriceCurve::Exponential => {
+            // Nothing to double, every pixel sits at the floor
+            if base <= 0 {
+                return 0.clamp(floor, cap) * (end - start) as i128;
+            }
+
+            let rate = u64::try_from(rate).unwrap_or(u64::MAX);
+            let mut cost: i128 = 0;
+            let mut n = start;
+
+            // Prices stop moving once they hit the cap, which a positive base reaches within 128 doublings
+            while n < end {
+                let doublings = n / rate;
+                let price = match doublings {
+                    0..=126 => base.checked_mul(1 << doublings).unwrap_or(cap),
+                    _ => cap,
+                }
+                .clamp(floor, cap);
+
+                if price == cap {
+                    cost += cap * (end - n) as i128;
+                    break;
+                }
+
+                let segment_end = (doublings + 1).saturating_mul(rate).min(end);
+
+                cost += price * (segment_end - n) as i128;
+                n = segment_end;
+            }
+
+            cost
+        }
+    }
+}
+
+pub fn colors_transfer(env: &Env, from: &Address, to: &Address, colors: &Vec<(Address, u32, u32)>) {
+    for (miner, color, amount) in colors.iter() {
+        let current_from_amount = read_color(env, &miner, from, color);
+        let current_to_amount = read_color(env, &miner, to, color);
+
+        if amount > current_from_amount {
+            panic_with_error!(env, Error::NotPermitted);
+        }
+
+        write_color(env, &miner, from, color, current_from_amount - amount);
+        write_color(env, &miner, to, color, current_to_amount + amount);
+    }
+}
+
+// Operators may spend any of the owner's colors, everyone else draws down a per (miner, color) allowance
+pub fn color_spend_allowance(
+    env: &Env,
+    owner: &Address,
+    spender: &Address,
+    miner: &Address,
+    color: u32,
+    amount: u32,
+) {
+    if read_color_operator(env, owner, spender) {
+        return;
+    }
+
+    let mut allowance = read_color_allowance(env, owner, spender, miner, color);
+
+    if amount > allowance.amount {
+        panic_with_error!(env, Error::NotAuthorized);
+    }
+
+    allowance.amount -= amount;
+
+    write_color_allowance(env, owner, spender, miner, color, &allowance);
+}
@@ -0,0 +1,132 @@
+use crate::{
+    contract::MAX_COMPOSITE_LAYERS,
+    glyphs::{
+        glyph_bundle_record, glyph_bundle_remove, glyph_escrow, glyph_escrow_release, glyph_status,
+        glyph_verify_ownership,
+    },
+    storage::persistent::{read_composite, read_glyph, remove_composite, write_composite},
+    types::{Bitmap, Error, GlyphStatus, Layer},
+};
+use soroban_sdk::{Address, Bytes, BytesN, Env, Map, Vec};
+
+// Stack glyphs `minter` owns into a single scene, a glyph may be used in more than one layer
+// The layered glyphs are held by the contract until the composite is decomposed
+pub fn glyph_compose(
+    env: &Env,
+    minter: Address,
+    to: Option<Address>,
+    layers: Vec<Layer>,
+) -> Result<BytesN<32>, Error> {
+    if layers.len() < 2 || layers.len() > MAX_COMPOSITE_LAYERS {
+        return Err(Error::NotPermitted);
+    }
+
+    let mut layer_bytes = Bytes::new(env);
+    let mut escrowed: Vec<BytesN<32>> = Vec::new(env);
+
+    for layer in layers.iter() {
+        layer_bytes.append(&layer.hash.clone().into());
+        layer_bytes.extend_from_array(&layer.x.to_be_bytes());
+        layer_bytes.extend_from_array(&layer.y.to_be_bytes());
+        layer_bytes.extend_from_array(&layer.z.to_be_bytes());
+
+        if !escrowed.contains(layer.hash.clone()) {
+            escrowed.push_back(layer.hash);
+        }
+    }
+
+    let hash = env.crypto().sha256(&layer_bytes).to_bytes();
+
+    if glyph_status(env, &hash) != GlyphStatus::Unknown {
+        return Err(Error::NotEmpty);
+    }
+
+    // Keep the scene addressable the same way a glyph is
+    let (width, height) = composite_size(env, &layers)?;
+
+    if width > u8::MAX as u32 || height > u8::MAX as u32 {
+        return Err(Error::NotPermitted);
+    }
+
+    glyph_escrow(env, &minter, &escrowed)?;
+
+    let owner = to.unwrap_or(minter.clone());
+
+    write_composite(env, &hash, &layers);
+    glyph_bundle_record(env, &minter, &owner, &hash);
+
+    Ok(hash)
+}
+
+// Break a composite back up, handing its layered glyphs to the composite's owner
+pub fn glyph_decompose(env: &Env, hash: &BytesN<32>) -> Result<(), Error> {
+    let layers = read_composite(env, hash).ok_or(Error::NotFound)?;
+    let owner = glyph_verify_ownership(env, hash, None);
+    let mut layer_hashes: Vec<BytesN<32>> = Vec::new(env);
+
+    for layer in layers.iter() {
+        layer_hashes.push_back(layer.hash);
+    }
+
+    glyph_escrow_release(env, &layer_hashes, &owner);
+    glyph_bundle_remove(env, &owner, hash);
+    remove_composite(env, hash);
+
+    Ok(())
+}
+
+// Flatten the layers lowest z first so each pixel ends up with the color of its topmost layer
+pub fn glyph_composite_bitmap(env: &Env, hash: &BytesN<32>) -> Result<Bitmap, Error> {
+    let layers = read_composite(env, hash).ok_or(Error::NotFound)?;
+    let (width, height) = composite_size(env, &layers)?;
+
+    // Stable insertion sort by z, composites only hold a handful of layers
+    let mut ordered: Vec<Layer> = Vec::new(env);
+
+    for layer in layers.iter() {
+        let mut position = ordered.len();
+
+        while position > 0 && ordered.get_unchecked(position - 1).z > layer.z {
+            position -= 1;
+        }
+
+        ordered.insert(position, layer);
+    }
+
+    let mut pixels: Map<u32, u32> = Map::new(env);
+
+    for layer in ordered.iter() {
+        let glyph = read_glyph(env, &layer.hash).ok_or(Error::NotFound)?;
+
+        for (_, color_indexes) in glyph.colors.iter() {
+            for (color, indexes) in color_indexes.iter() {
+                for index in indexes.iter() {
+                    let x = layer.x + index % glyph.width;
+                    let y = layer.y + index / glyph.width;
+
+                    pixels.set(y * width + x, color);
+                }
+            }
+        }
+    }
+
+    Ok(Bitmap {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn composite_size(env: &Env, layers: &Vec<Layer>) -> Result<(u32, u32), Error> {
+    let mut width: u32 = 0;
+    let mut height: u32 = 0;
+
+    for layer in layers.iter() {
+        let glyph = read_glyph(env, &layer.hash).ok_or(Error::NotFound)?;
+
+        width = width.max(layer.x.saturating_add(glyph.width));
+        height = height.max(layer.y.saturating_add(glyph.length.div_ceil(glyph.width)));
+    }
+
+    Ok((width, height))
+}
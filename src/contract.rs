@@ -1,25 +1,69 @@
-use core::ops::Mul;
-
 // extern crate std;
 
 use soroban_sdk::{
-    contract, contractimpl, panic_with_error, token, Address, BytesN, Env, Map, Vec,
+    contract, contractimpl, panic_with_error, Address, Bytes, BytesN, Env, IntoVal, Map, String,
+    Vec,
 };
 
 use crate::{
-    glyphs::{glyph_store, glyph_verify_ownership},
-    interface::{ColorGlyphTrait, ColorsInterface, Exchange, GlyphInterface},
-    offers::{offer_delete, offer_post, offers_get},
+    animations::{glyph_animate, glyph_animation_svg, glyph_unanimate},
+    canvas::{canvas_create, canvas_freeze, canvas_paint, canvas_pixels},
+    colors::{
+        color_spend_allowance, colors_mine, colors_mine_cost, colors_transfer, mine_pricing_verify,
+    },
+    composites::{glyph_compose, glyph_composite_bitmap, glyph_decompose},
+    events,
+    glyphs::{
+        glyph_colors_return, glyph_contributor_set, glyph_mine_and_mint, glyph_mint,
+        glyph_mint_cancel, glyph_mint_quote, glyph_mint_variant, glyph_move, glyph_parent_set,
+        glyph_repaint, glyph_reserve, glyph_reveal, glyph_safe_transfer, glyph_status,
+        glyph_transfer, glyph_verify_ownership,
+    },
+    interface::{
+        CanvasInterface, ColorGlyphTrait, ColorsInterface, Exchange, GlyphInterface,
+        NonFungibleToken,
+    },
+    offers::{
+        offer_delete, offer_post, offers_get, offers_refund, offers_reindex, offers_stranded,
+    },
     storage::{
         instance::*,
         persistent::{
-            read_color, read_glyph_or_default, read_glyph_or_error, remove_glyph_offer, write_color,
+            read_animation, read_canvas, read_canvas_snapshot, read_color, read_color_allowance,
+            read_color_operator, read_color_supply, read_color_total, read_composite, read_glyph,
+            read_glyph_approval, read_glyph_children, read_glyph_contributors, read_glyph_operator,
+            read_glyph_or_error, read_glyph_owner, read_glyph_parent, read_glyph_reservation,
+            read_owner_colors, read_owner_glyph_count, read_owner_glyphs, read_stats,
+            remove_glyph_approval, remove_glyph_offer, remove_glyph_owner, remove_owner_glyph,
+            write_color_allowance, write_color_operator, write_glyph_approval,
+            write_glyph_derive_locked, write_glyph_operator, write_stats,
         },
     },
-    types::{Error, Glyph, Offer, StorageKey},
+    types::{
+        Bitmap, Canvas, CanvasPixel, ColorAllowance, ColorSupply, Error, Glyph, GlyphApproval,
+        GlyphStatus, Layer, MinePricing, MintQuote, Offer, Stats, StorageKey, Transform,
+    },
 };
 
 pub const MAX_BIT24_SIZE: usize = 40 * 40 * 3 + 1;
+pub const MAX_BASE_URI_SIZE: usize = 128;
+pub const MAX_ANIMATION_FRAMES: u32 = 16;
+pub const MAX_COMPOSITE_LAYERS: u32 = 16;
+pub const MAX_RESERVATION_LEDGERS: u32 = 12 * 60 * 24; // A day's worth of ledgers
+pub const COLOR_PAYMENT_ENTRIES: u32 = 6; // The balance, owner total, color supply and 3 registry entries for a newly held pair
+
+pub const NAME: &str = "Colorglyph";
+pub const SYMBOL: &str = "GLYPH";
+
+// Lowercase hex of `bytes` into the first `bytes.len() * 2` bytes of `out`
+pub fn hex_encode(bytes: &[u8], out: &mut [u8]) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    for (i, byte) in bytes.iter().enumerate() {
+        out[i * 2] = HEX[(byte >> 4) as usize];
+        out[i * 2 + 1] = HEX[(byte & 0x0f) as usize];
+    }
+}
 
 #[contract]
 pub struct ColorGlyph;
@@ -43,6 +87,7 @@ impl ColorGlyphTrait for ColorGlyph {
         let max_payment_count: u32 = 23; // 25 - the glyph and any potential offers
         let minter_royalty_rate: i128 = 3; // 3%
         let miner_royalty_rate: i128 = 2; // 2%
+        let derivative_royalty_rate: i128 = 1; // 1%
 
         write_owner_address(&env, &owner_address);
         write_token_address(&env, &token_address);
@@ -52,6 +97,7 @@ impl ColorGlyphTrait for ColorGlyph {
         write_mine_multiplier(&env, &mine_multiplier);
         write_minter_royalty_rate(&env, &minter_royalty_rate);
         write_miner_royalty_rate(&env, &miner_royalty_rate);
+        write_derivative_royalty_rate(&env, &derivative_royalty_rate);
 
         env.storage()
             .instance()
@@ -98,11 +144,61 @@ impl ColorGlyphTrait for ColorGlyph {
         }
     }
 
+    // `token_uri` is the base URI followed by the hex encoded hash
+    fn base_uri_set(env: Env, base_uri: String) -> Result<(), Error> {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        if base_uri.len() as usize > MAX_BASE_URI_SIZE {
+            return Err(Error::NotPermitted);
+        }
+
+        write_base_uri(&env, &base_uri);
+
+        Ok(())
+    }
+
+    // The percentage of a derivative's sales paid to the minter of its parent
+    // Together with the minter and miner cuts it can't take more than the whole sale
+    fn derivative_royalty_rate_set(env: Env, rate: i128) -> Result<(), Error> {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        if rate < 0 || rate + read_minter_royalty_rate(&env) + read_miner_royalty_rate(&env) > 100 {
+            return Err(Error::NotPermitted);
+        }
+
+        write_derivative_royalty_rate(&env, &rate);
+
+        Ok(())
+    }
+
+    // Setting None goes back to a flat `MineMultiplier` per pixel
+    fn mine_pricing_set(env: Env, mine_pricing: Option<MinePricing>) -> Result<(), Error> {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        if let Some(mine_pricing) = &mine_pricing {
+            mine_pricing_verify(mine_pricing)?;
+        }
+
+        write_mine_pricing(&env, &mine_pricing);
+
+        Ok(())
+    }
+    fn mine_pricing(env: Env) -> Option<MinePricing> {
+        read_mine_pricing(&env)
+    }
+
     fn upgrade(env: Env, hash: BytesN<32>) {
         let owner = read_owner_address(&env);
         owner.require_auth();
         env.deployer().update_current_contract_wasm(hash);
     }
+
+    fn stats(env: Env) -> Stats {
+        read_stats(&env)
+    }
 }
 
 #[contractimpl]
@@ -113,52 +209,85 @@ impl ColorsInterface for ColorGlyph {
         colors: Map<u32, u32>,
         miner: Option<Address>,
         to: Option<Address>,
-    ) {
+        max_cost: Option<i128>,
+    ) -> Result<i128, Error> {
         source.require_auth();
 
         let miner = miner.unwrap_or(source.clone());
         let to = to.unwrap_or(source.clone());
 
-        let mut pay_amount: u32 = 0;
+        colors_mine(&env, &source, &miner, &to, &colors, max_cost)
+    }
+    fn colors_mine_quote(env: Env, colors: Map<u32, u32>) -> i128 {
+        colors_mine_cost(&env, &colors)
+    }
 
-        for (color, amount) in colors.iter() {
-            let current_amount = read_color(&env, &miner, &to, color);
-            write_color(&env, &miner, &to, color, current_amount + amount);
+    fn colors_transfer(env: Env, from: Address, to: Address, colors: Vec<(Address, u32, u32)>) {
+        from.require_auth();
 
-            pay_amount += amount;
-        }
+        colors_transfer(&env, &from, &to, &colors);
 
-        // crate::events::colors_mine(&env, &miner, &to, colors);
+        // crate::events::colors_transfer(&env, &from, &to, colors);
+    }
 
-        let token_address = read_token_address(&env);
-        let fee_address = read_fee_address(&env);
+    fn colors_transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        colors: Vec<(Address, u32, u32)>,
+    ) {
+        spender.require_auth();
 
-        let token = token::Client::new(&env, &token_address);
-        let mine_multiplier = read_mine_multiplier(&env);
+        for (miner, color, amount) in colors.iter() {
+            color_spend_allowance(&env, &from, &spender, &miner, color, amount);
+        }
 
-        token.transfer(
-            &source,
-            &fee_address,
-            &(pay_amount as i128).mul(mine_multiplier),
-        );
+        colors_transfer(&env, &from, &to, &colors);
     }
 
-    fn colors_transfer(env: Env, from: Address, to: Address, colors: Vec<(Address, u32, u32)>) {
-        from.require_auth();
+    fn colors_approve(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        miner: Address,
+        color: u32,
+        amount: u32,
+        live_until_ledger: u32,
+    ) {
+        owner.require_auth();
 
-        for (miner, color, amount) in colors.iter() {
-            let current_from_amount = read_color(&env, &miner, &from, color);
-            let current_to_amount = read_color(&env, &miner, &to, color);
+        write_color_allowance(
+            &env,
+            &owner,
+            &spender,
+            &miner,
+            color,
+            &ColorAllowance {
+                amount,
+                live_until_ledger,
+            },
+        );
+    }
 
-            if amount > current_from_amount {
-                panic_with_error!(env, Error::NotPermitted);
-            }
+    fn colors_allowance(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        miner: Address,
+        color: u32,
+    ) -> ColorAllowance {
+        read_color_allowance(&env, &owner, &spender, &miner, color)
+    }
 
-            write_color(&env, &miner, &from, color, current_from_amount - amount);
-            write_color(&env, &miner, &to, color, current_to_amount + amount);
-        }
+    fn colors_set_operator(env: Env, owner: Address, operator: Address, live_until_ledger: u32) {
+        owner.require_auth();
 
-        // crate::events::colors_transfer(&env, &from, &to, colors);
+        write_color_operator(&env, &owner, &operator, live_until_ledger);
+    }
+
+    fn colors_is_operator(env: Env, owner: Address, operator: Address) -> bool {
+        read_color_operator(&env, &owner, &operator)
     }
 
     fn color_balance(env: Env, owner: Address, color: u32, miner: Option<Address>) -> u32 {
@@ -166,6 +295,42 @@ impl ColorsInterface for ColorGlyph {
 
         read_color(&env, &miner, &owner, color)
     }
+
+    fn color_balances(env: Env, owner: Address, colors: Vec<(Option<Address>, u32)>) -> Vec<u32> {
+        let mut balances = Vec::new(&env);
+
+        for (miner, color) in colors.iter() {
+            let miner = miner.unwrap_or(owner.clone());
+
+            balances.push_back(read_color(&env, &miner, &owner, color));
+        }
+
+        balances
+    }
+
+    fn color_balance_total(env: Env, owner: Address, color: u32) -> u32 {
+        read_color_total(&env, &owner, color)
+    }
+
+    fn color_supply(env: Env, color: u32) -> ColorSupply {
+        read_color_supply(&env, color)
+    }
+
+    // `cursor` and `limit` page through every pair the owner has held, spent out pairs are skipped
+    // so a page can come back short of `limit` without being the last
+    fn colors_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<(Address, u32, u32)> {
+        let mut colors = Vec::new(&env);
+
+        for (miner, color) in read_owner_colors(&env, &owner, cursor, limit).iter() {
+            let amount = read_color(&env, &miner, &owner, color);
+
+            if amount > 0 {
+                colors.push_back((miner, color, amount));
+            }
+        }
+
+        colors
+    }
 }
 
 #[contractimpl]
@@ -177,165 +342,117 @@ impl GlyphInterface for ColorGlyph {
         to: Option<Address>,
         colors: Map<Address, Map<u32, Vec<u32>>>,
         width: Option<u32>,
-    ) {
-        let mut glyph = read_glyph_or_default(&env, &hash);
+    ) -> Result<(), Error> {
+        minter.require_auth();
 
-        // Only mint if the glyph hasn't yet been minted
-        if glyph.length != 0 {
-            panic_with_error!(env, Error::NotEmpty);
-        }
+        glyph_mint(&env, hash, minter.clone(), minter, to, colors, width)
+    }
+    fn glyph_mint_from(
+        env: Env,
+        hash: BytesN<32>,
+        spender: Address,
+        from: Address,
+        to: Option<Address>,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: Option<u32>,
+    ) -> Result<(), Error> {
+        spender.require_auth();
 
-        let glyph_owner_key = StorageKey::GlyphOwner(hash.clone());
-        let new_owner = match to.clone() {
-            Some(address) => address,
-            None => minter.clone(),
-        };
+        glyph_mint(&env, hash, spender, from, to, colors, width)
+    }
+    fn glyph_mine_and_mint(
+        env: Env,
+        hash: BytesN<32>,
+        minter: Address,
+        to: Option<Address>,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: Option<u32>,
+        max_cost: Option<i128>,
+    ) -> Result<i128, Error> {
+        minter.require_auth();
 
-        // Starting the mint, assign an owner
-        if glyph.colors.is_empty() {
-            env.storage().persistent().set(&glyph_owner_key, &new_owner);
-        } else {
-            let existing_owner = glyph_verify_ownership(&env, &glyph_owner_key);
+        glyph_mine_and_mint(&env, hash, minter, to, colors, width, max_cost)
+    }
+    fn glyph_mint_quote(
+        env: Env,
+        minter: Address,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: u32,
+    ) -> Result<MintQuote, Error> {
+        glyph_mint_quote(&env, &minter, &colors, width)
+    }
+    fn glyph_transfer(env: Env, from: Address, to: Address, hash: BytesN<32>) -> Result<(), Error> {
+        let owner = read_glyph_owner(&env, &hash).ok_or(Error::NotFound)?;
 
-            if existing_owner != new_owner {
-                env.storage().persistent().set(&glyph_owner_key, &new_owner);
-            }
-        }
+        // Scope the signature to this exact glyph and recipient
+        from.require_auth_for_args((to.clone(), hash.clone()).into_val(&env));
 
-        // spend colors
-        for (miner, color_indexes) in colors.iter() {
-            let mut skip = false;
-
-            for (color, indexes) in color_indexes.iter() {
-                let current_color_amount = read_color(&env, &miner, &minter, color);
-                write_color(
-                    &env,
-                    &miner,
-                    &minter,
-                    color,
-                    current_color_amount - indexes.len(),
-                );
-
-                // crate::events::colors_out(&env, &miner, &minter, color, indexes.len());
-
-                if !skip {
-                    match glyph.colors.get(miner.clone()) {
-                        Some(result) => match result {
-                            mut color_indexes_ => match color_indexes_.get(color) {
-                                // Existing miner and color
-                                Some(result) => match result {
-                                    mut indexes_ => {
-                                        indexes_.append(&indexes);
-                                        color_indexes_.set(color, indexes_);
-                                        glyph.colors.set(miner.clone(), color_indexes_);
-                                    }
-                                },
-                                // Existing miner no color
-                                None => {
-                                    color_indexes_.set(color, indexes);
-                                    glyph.colors.set(miner.clone(), color_indexes_);
-                                }
-                            },
-                        },
-                        // No miner (or no exisiting Colors)
-                        None => {
-                            glyph.colors.set(miner.clone(), color_indexes.clone());
-                            // We set a skip vs using break to ensure we continue to bill for the spent colors
-                            skip = true; // we need to break here otherwise we continue looping inside this nested color loop which we've already fully added
-                        }
-                    }
-                }
+        glyph_move(&env, &owner, &from, &to, &hash)
+    }
+    fn glyph_scrape(env: Env, to: Option<Address>, hash: BytesN<32>) -> Result<(), Error> {
+        // Pending builds are unwound with `glyph_mint_cancel` rather than scraped
+        let mut glyph = match read_glyph(&env, &hash) {
+            Some(glyph) => glyph,
+            None if read_animation(&env, &hash).is_some()
+                || read_composite(&env, &hash).is_some() =>
+            {
+                return Err(Error::NotPermitted)
             }
-        }
-
-        match width {
-            // We are storing the glyph
-            Some(width) => {
-                let computed_hash = glyph_store(&env, minter.clone(), glyph.colors, width as u8);
-
-                // println!("HASH: {:?}", computed_hash);
-
-                if hash != computed_hash {
-                    panic_with_error!(env, Error::NotPermitted);
-                }
+            None => return Err(Error::NotFound),
+        };
 
-                // crate::events::minted_event(&env, &minter, to, &hash);
+        if glyph.length == 0 {
+            if glyph.colors.is_empty() {
+                return Err(Error::AlreadyScraped);
             }
-            // We are building the glyph
-            None => {
-                let glyph_key = StorageKey::Glyph(hash.clone());
-
-                env.storage()
-                    .persistent()
-                    .set::<StorageKey, Glyph>(&glyph_key, &glyph);
 
-                // crate::events::minting_event(&env, &minter);
+            if glyph.width == 0 {
+                return Err(Error::NotMinted);
             }
         }
-    }
-    fn glyph_transfer(env: Env, to: Address, hash: BytesN<32>) {
-        let glyph_owner_key = StorageKey::GlyphOwner(hash.clone());
-
-        glyph_verify_ownership(&env, &glyph_owner_key);
-
-        env.storage().persistent().set(&glyph_owner_key, &to);
 
-        // crate::events::transfer_glyph_event(&env, &to, &hash);
-    }
-    fn glyph_scrape(env: Env, to: Option<Address>, hash: BytesN<32>) {
-        let owner_key = StorageKey::GlyphOwner(hash.clone());
-        let owner = glyph_verify_ownership(&env, &owner_key);
+        // A canvas snapshot's colors are still locked in the canvas
+        if read_canvas_snapshot(&env, &hash).is_some() {
+            return Err(Error::NotPermitted);
+        }
 
-        // TODO don't allow scraping a fully scraped glyph, either remove the owner or check for empty colors
+        let owner = glyph_verify_ownership(&env, &hash, None);
 
-        // Ensure we don't start a scrape while there's a pending mint, otherwise we'll overwrite the pending with the new
         // We use the Address vs the BytesN<32> as the key in order to maintain ownership of the Colors
         // If we wanted to support scraping multiple glyphs at once we'd need to track ownership another way
 
-        let mut glyph = read_glyph_or_error(&env, &hash);
-
         // Remove all glyph sell offers
         remove_glyph_offer(&env, &hash);
 
+        // A scraped glyph is no longer owned as a glyph, only its remaining colors are
+        if glyph.length != 0 {
+            remove_owner_glyph(&env, &owner, &hash);
+
+            let mut stats = read_stats(&env);
+            stats.glyphs_scraped += 1;
+            write_stats(&env, &stats);
+        }
+
         // crate::events::scrape_glyph_event(&env, &owner, to.clone(), &hash);
 
         // loop through the glyph colors and send them to `to`
-        let mut payment_count: u8 = 0;
         let to_address = to.unwrap_or(owner.clone());
+        let max_payment_count = read_max_payment_count(&env);
 
-        let max_payment_count = read_max_payment_count(&env) as u8;
-
-        for (miner, mut colors_indexes) in glyph.colors.iter() {
-            for (color, indexes) in colors_indexes.iter() {
-                let current_amount = read_color(&env, &miner, &to_address, color);
+        // Hand escrowed bids back first so they're all refunded by the time the colors run out
+        let refund_count = offers_refund(&env, &hash, max_payment_count);
+        let budget = max_payment_count.saturating_sub(refund_count);
 
-                write_color(
-                    &env,
-                    &miner,
-                    &to_address,
-                    color,
-                    current_amount + indexes.len(),
-                );
-
-                colors_indexes.remove(color);
-                payment_count += 1;
-
-                if payment_count >= max_payment_count {
-                    break;
-                }
-
-                // crate::events::color_in_event(&env, &miner, &to_address, color, indexes.len());
-            }
+        if refund_count == 0 || budget >= COLOR_PAYMENT_ENTRIES {
+            glyph_colors_return(&env, &mut glyph.colors, &to_address, budget);
+        }
 
-            if colors_indexes.is_empty() {
-                glyph.colors.remove(miner);
-            } else {
-                glyph.colors.set(miner, colors_indexes);
-            }
+        // The emptied Glyph entry is kept so the hash still reads as scraped
+        if glyph.colors.is_empty() {
+            remove_glyph_owner(&env, &hash);
+            remove_glyph_approval(&env, &hash);
 
-            if payment_count >= max_payment_count {
-                break;
-            }
+            events::scrape_glyph_complete_event(&env, &owner, &hash);
         }
 
         let glyph_key = StorageKey::Glyph(hash.clone());
@@ -349,25 +466,296 @@ impl GlyphInterface for ColorGlyph {
             },
         );
 
-        // NOTE Not sure we actually need to remove the glyph owner as we won't check for it when re-minting if the glyph.colors is empty
-        // if glyph.colors.is_empty() {
-        //     remove_glyph_owner(&env, &hash);
-        // }
+        Ok(())
+    }
+    fn glyph_mint_cancel(env: Env, hash: BytesN<32>) -> Result<(), Error> {
+        glyph_mint_cancel(&env, &hash)
+    }
+    fn glyph_reserve(
+        env: Env,
+        reserver: Address,
+        commitment: BytesN<32>,
+        live_until_ledger: u32,
+    ) -> Result<(), Error> {
+        reserver.require_auth();
+
+        glyph_reserve(&env, &reserver, &commitment, live_until_ledger)
+    }
+    fn glyph_reveal(
+        env: Env,
+        reserver: Address,
+        hash: BytesN<32>,
+        salt: BytesN<32>,
+    ) -> Result<(), Error> {
+        reserver.require_auth();
+
+        glyph_reveal(&env, &reserver, &hash, &salt)
+    }
+    fn glyph_reservation(env: Env, hash: BytesN<32>) -> Option<Address> {
+        read_glyph_reservation(&env, &hash)
+    }
+    fn glyph_mint_variant(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        source_hash: BytesN<32>,
+        transform: Transform,
+    ) -> Result<BytesN<32>, Error> {
+        minter.require_auth();
+
+        glyph_mint_variant(&env, minter, to, &source_hash, transform)
+    }
+    fn glyph_animate(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        frames: Vec<(BytesN<32>, u32)>,
+    ) -> Result<BytesN<32>, Error> {
+        minter.require_auth();
+
+        glyph_animate(&env, minter, to, frames)
+    }
+    fn glyph_unanimate(env: Env, hash: BytesN<32>) -> Result<(), Error> {
+        glyph_unanimate(&env, &hash)
+    }
+    fn glyph_animation(env: Env, hash: BytesN<32>) -> Result<Vec<(BytesN<32>, u32)>, Error> {
+        read_animation(&env, &hash).ok_or(Error::NotFound)
+    }
+    fn glyph_animation_svg(env: Env, hash: BytesN<32>) -> Result<Bytes, Error> {
+        glyph_animation_svg(&env, &hash)
+    }
+    fn glyph_compose(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        layers: Vec<Layer>,
+    ) -> Result<BytesN<32>, Error> {
+        minter.require_auth();
+
+        glyph_compose(&env, minter, to, layers)
+    }
+    fn glyph_decompose(env: Env, hash: BytesN<32>) -> Result<(), Error> {
+        glyph_decompose(&env, &hash)
+    }
+    fn glyph_composite(env: Env, hash: BytesN<32>) -> Result<Vec<Layer>, Error> {
+        read_composite(&env, &hash).ok_or(Error::NotFound)
+    }
+    fn glyph_composite_bitmap(env: Env, hash: BytesN<32>) -> Result<Bitmap, Error> {
+        glyph_composite_bitmap(&env, &hash)
+    }
+    fn glyph_contributor_set(
+        env: Env,
+        hash: BytesN<32>,
+        contributor: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        glyph_contributor_set(&env, &hash, contributor, allowed)
+    }
+    fn glyph_contributors(env: Env, hash: BytesN<32>) -> Map<Address, u32> {
+        read_glyph_contributors(&env, &hash)
+    }
+    fn glyph_parent_set(env: Env, hash: BytesN<32>, parent: BytesN<32>) -> Result<(), Error> {
+        glyph_parent_set(&env, &hash, &parent)
+    }
+    fn glyph_derive_lock(env: Env, parent: BytesN<32>, locked: bool) {
+        glyph_verify_ownership(&env, &parent, None);
+
+        write_glyph_derive_locked(&env, &parent, locked);
+    }
+    fn glyph_parent(env: Env, hash: BytesN<32>) -> Option<BytesN<32>> {
+        read_glyph_parent(&env, &hash)
+    }
+    fn glyph_children(env: Env, hash: BytesN<32>, cursor: u32, limit: u32) -> Vec<BytesN<32>> {
+        read_glyph_children(&env, &hash, cursor, limit)
+    }
+    fn glyph_repaint(
+        env: Env,
+        hash: BytesN<32>,
+        remove: Map<Address, Map<u32, Vec<u32>>>,
+        add: Map<Address, Map<u32, Vec<u32>>>,
+        width: u32,
+    ) -> Result<BytesN<32>, Error> {
+        glyph_repaint(&env, &hash, remove, add, width)
     }
     fn glyph_get(env: Env, hash: BytesN<32>) -> Result<Glyph, Error> {
         Ok(read_glyph_or_error(&env, &hash))
     }
+    fn glyph_status(env: Env, hash: BytesN<32>) -> GlyphStatus {
+        glyph_status(&env, &hash)
+    }
+    fn glyphs_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<BytesN<32>> {
+        read_owner_glyphs(&env, &owner, cursor, limit)
+    }
+    fn glyph_count_of(env: Env, owner: Address) -> u32 {
+        read_owner_glyph_count(&env, &owner)
+    }
+}
+
+#[contractimpl]
+impl CanvasInterface for ColorGlyph {
+    fn canvas_create(
+        env: Env,
+        owner: Address,
+        width: u32,
+        height: u32,
+        burn: bool,
+    ) -> Result<u32, Error> {
+        owner.require_auth();
+
+        canvas_create(&env, owner, width, height, burn)
+    }
+    fn canvas_paint(
+        env: Env,
+        painter: Address,
+        id: u32,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+    ) -> Result<(), Error> {
+        painter.require_auth();
+
+        canvas_paint(&env, id, painter, colors)
+    }
+    fn canvas_get(env: Env, id: u32) -> Result<Canvas, Error> {
+        read_canvas(&env, id).ok_or(Error::NotFound)
+    }
+    fn canvas_pixels(
+        env: Env,
+        id: u32,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<Vec<(u32, CanvasPixel)>, Error> {
+        canvas_pixels(&env, id, cursor, limit)
+    }
+    fn canvas_freeze(env: Env, id: u32, to: Option<Address>) -> Result<BytesN<32>, Error> {
+        canvas_freeze(&env, id, to)
+    }
 }
 
 #[contractimpl]
 impl Exchange for ColorGlyph {
-    fn offer_post(env: Env, sell: Offer, buy: Offer) -> Result<(), Error> {
-        offer_post(&env, sell, buy)
+    fn offer_post(
+        env: Env,
+        sell: Offer,
+        buy: Offer,
+        spender: Option<Address>,
+    ) -> Result<(), Error> {
+        offer_post(&env, sell, buy, spender)
     }
-    fn offer_delete(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error> {
-        offer_delete(&env, sell, buy)
+    fn offer_delete(
+        env: Env,
+        sell: Offer,
+        buy: Option<Offer>,
+        spender: Option<Address>,
+    ) -> Result<(), Error> {
+        offer_delete(&env, sell, buy, spender)
     }
     fn offers_get(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error> {
         offers_get(&env, sell, buy)
     }
+    fn offers_stranded(env: Env, hash: BytesN<32>) -> Vec<Offer> {
+        offers_stranded(&env, &hash)
+    }
+    fn offers_reindex(env: Env, hash: BytesN<32>, keys: Vec<(Address, i128)>) {
+        offers_reindex(&env, &hash, &keys)
+    }
+}
+
+#[contractimpl]
+impl NonFungibleToken for ColorGlyph {
+    fn balance(env: Env, owner: Address) -> u32 {
+        read_owner_glyph_count(&env, &owner)
+    }
+    fn owner_of(env: Env, hash: BytesN<32>) -> Result<Address, Error> {
+        // Builds and part scraped glyphs have an owner on record but no token to own
+        match glyph_status(&env, &hash) {
+            GlyphStatus::Building(_) | GlyphStatus::Scraping(_, _) => Err(Error::NotMinted),
+            _ => read_glyph_owner(&env, &hash).ok_or(Error::NotFound),
+        }
+    }
+    fn transfer(env: Env, from: Address, to: Address, hash: BytesN<32>) -> Result<(), Error> {
+        glyph_transfer(&env, &from, &from, &to, &hash)
+    }
+    fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        glyph_transfer(&env, &spender, &from, &to, &hash)
+    }
+    fn safe_transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        hash: BytesN<32>,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        glyph_safe_transfer(&env, &from, &from, &to, &hash, &data)
+    }
+    fn safe_transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        hash: BytesN<32>,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        glyph_safe_transfer(&env, &spender, &from, &to, &hash, &data)
+    }
+    fn approve(
+        env: Env,
+        owner: Address,
+        approved: Address,
+        hash: BytesN<32>,
+        live_until_ledger: u32,
+    ) -> Result<(), Error> {
+        let glyph_owner = read_glyph_owner(&env, &hash).ok_or(Error::NotFound)?;
+
+        if glyph_owner != owner {
+            return Err(Error::NotAuthorized);
+        }
+
+        owner.require_auth();
+
+        write_glyph_approval(
+            &env,
+            &hash,
+            &GlyphApproval {
+                approved,
+                live_until_ledger,
+            },
+        );
+
+        Ok(())
+    }
+    fn get_approved(env: Env, hash: BytesN<32>) -> Option<Address> {
+        read_glyph_approval(&env, &hash)
+    }
+    fn set_approval_for_all(env: Env, owner: Address, operator: Address, live_until_ledger: u32) {
+        owner.require_auth();
+
+        write_glyph_operator(&env, &owner, &operator, live_until_ledger);
+    }
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        read_glyph_operator(&env, &owner, &operator)
+    }
+    fn name(env: Env) -> String {
+        String::from_str(&env, NAME)
+    }
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, SYMBOL)
+    }
+    // Only tokens `owner_of` would answer for have a URI
+    fn token_uri(env: Env, hash: BytesN<32>) -> Result<String, Error> {
+        Self::owner_of(env.clone(), hash.clone())?;
+
+        let base_uri = read_base_uri(&env);
+        let base_len = base_uri.len() as usize;
+        let mut uri = [0u8; MAX_BASE_URI_SIZE + 64];
+
+        base_uri.copy_into_slice(&mut uri[..base_len]);
+        hex_encode(&hash.to_array(), &mut uri[base_len..]);
+
+        Ok(String::from_bytes(&env, &uri[..base_len + 64]))
+    }
 }
@@ -1,3 +1,6 @@
+// Events without a call site yet are kept as first written for when they get one
+#![allow(dead_code, clippy::needless_borrow)]
+
 use soroban_sdk::{symbol_short, Address, BytesN, Env, Map, Symbol, Vec};
 
 use crate::types::Offer;
@@ -43,11 +46,12 @@ pub fn transfer_colors_event(env: &Env, from: &Address, to: &Address) {
 }
 
 // Note this event has changed to avoid unnecessary enlargement of soroban meta.
-pub fn transfer_glyph_event(env: &Env, to: &Address, glyph_hash: &BytesN<32>) {
+pub fn transfer_glyph_event(env: &Env, from: &Address, to: &Address, glyph_hash: &BytesN<32>) {
     env.events().publish(
         (
-            Symbol::new(&env, "transfer_glyph"),
+            Symbol::new(env, "transfer_glyph"),
             glyph_hash.clone(),
+            from.clone(),
             to.clone(),
         ),
         (),
@@ -77,6 +81,29 @@ pub fn scrape_glyph_event(
     );
 }
 
+pub fn scrape_glyph_complete_event(env: &Env, owner: &Address, glyph_hash: &BytesN<32>) {
+    env.events().publish(
+        (Symbol::new(env, "scrape_glyph_complete"), owner.clone()),
+        glyph_hash.clone(),
+    );
+}
+
+pub fn repaint_glyph_event(
+    env: &Env,
+    owner: &Address,
+    glyph_hash: &BytesN<32>,
+    new_glyph_hash: &BytesN<32>,
+) {
+    env.events().publish(
+        (
+            Symbol::new(env, "repaint_glyph"),
+            owner.clone(),
+            glyph_hash.clone(),
+        ),
+        new_glyph_hash.clone(),
+    );
+}
+
 pub fn color_in_event(
     env: &Env,
     miner: &Address,
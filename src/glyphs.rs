@@ -1,15 +1,908 @@
 use crate::{
-    contract::MAX_BIT24_SIZE,
-    types::{Error, Glyph, StorageKey},
+    colors::{color_spend_allowance, colors_mine, colors_mine_cost},
+    contract::{COLOR_PAYMENT_ENTRIES, MAX_BIT24_SIZE, MAX_RESERVATION_LEDGERS},
+    events,
+    interface::GlyphReceiverClient,
+    offers::offers_escrowed,
+    storage::{
+        instance::read_max_payment_count,
+        persistent::{
+            add_glyph_child, add_owner_glyph, read_animation, read_canvas_snapshot, read_color,
+            read_composite, read_glyph, read_glyph_approval, read_glyph_contributors,
+            read_glyph_derive_locked, read_glyph_minter, read_glyph_operator,
+            read_glyph_or_default, read_glyph_owner, read_glyph_parent, read_glyph_reservation,
+            read_glyph_spends, read_reservation_commitment, read_stats, remove_glyph_approval,
+            remove_glyph_child, remove_glyph_offer, remove_glyph_owner, remove_glyph_parent,
+            remove_glyph_reservation, remove_owner_glyph, remove_reservation_commitment,
+            write_color, write_color_locked, write_color_unlocked, write_glyph_contributors,
+            write_glyph_minter, write_glyph_owner, write_glyph_parent, write_glyph_reservation,
+            write_glyph_spends, write_reservation_commitment, write_stats,
+        },
+    },
+    types::{Error, Glyph, GlyphReservation, GlyphStatus, MintQuote, StorageKey, Transform},
 };
 use soroban_sdk::{panic_with_error, Address, Bytes, BytesN, Env, Map, Vec};
 
-pub fn glyph_store(
+// Mine just the colors `minter` is short of for this build and then mint it in the same call
+// Returns the amount charged for the mining, which across all miners can't go over `max_cost`
+pub fn glyph_mine_and_mint(
     env: &Env,
+    hash: BytesN<32>,
     minter: Address,
+    to: Option<Address>,
     colors: Map<Address, Map<u32, Vec<u32>>>,
+    width: Option<u32>,
+    max_cost: Option<i128>,
+) -> Result<i128, Error> {
+    let mut charged: i128 = 0;
+
+    for (miner, shortfall) in glyph_color_shortfall(env, &minter, &colors).iter() {
+        let max_miner_cost = max_cost.map(|max_cost| max_cost - charged);
+
+        charged += colors_mine(env, &minter, &miner, &minter, &shortfall, max_miner_cost)?;
+    }
+
+    glyph_mint(env, hash, minter.clone(), minter, to, colors, width)?;
+
+    Ok(charged)
+}
+
+// Dry run of a full mint, what the glyph would hash to and what `minter` would have to mine first
+pub fn glyph_mint_quote(
+    env: &Env,
+    minter: &Address,
+    colors: &Map<Address, Map<u32, Vec<u32>>>,
+    width: u32,
+) -> Result<MintQuote, Error> {
+    if width == 0 {
+        return Err(Error::MissingWidth);
+    }
+
+    if width > u8::MAX as u32 {
+        return Err(Error::NotPermitted);
+    }
+
+    // Every pixel has to fit in the 40x40 bitmap the hash is taken over
+    for (_, color_indexes) in colors.iter() {
+        for (_, indexes) in color_indexes.iter() {
+            if indexes
+                .iter()
+                .any(|index| index >= (MAX_BIT24_SIZE as u32 - 1) / 3)
+            {
+                return Err(Error::NotPermitted);
+            }
+        }
+    }
+
+    let (hash, _) = glyph_hash(env, colors, width as u8);
+
+    // Only a hash `minter` could actually mint, a build of their own included
+    match glyph_status(env, &hash) {
+        GlyphStatus::Unknown | GlyphStatus::Scraped => {}
+        GlyphStatus::Building(_) if read_glyph_owner(env, &hash) == Some(minter.clone()) => {}
+        _ => return Err(Error::NotEmpty),
+    }
+
+    glyph_require_reserver(env, &hash, minter)?;
+
+    let mut shortfall: Vec<(Address, u32, u32)> = Vec::new(env);
+    let mut color_shortfall: Map<u32, u32> = Map::new(env);
+
+    for (miner, miner_shortfall) in glyph_color_shortfall(env, minter, colors).iter() {
+        for (color, amount) in miner_shortfall.iter() {
+            shortfall.push_back((miner.clone(), color, amount));
+            color_shortfall.set(color, color_shortfall.get(color).unwrap_or(0) + amount);
+        }
+    }
+
+    // Supply is shared across miners so each color is priced over its whole shortfall at once
+    Ok(MintQuote {
+        hash,
+        shortfall,
+        cost: colors_mine_cost(env, &color_shortfall),
+        miners: colors.len(),
+    })
+}
+
+// Per miner, how many of each color `minter` still needs for `colors`
+fn glyph_color_shortfall(
+    env: &Env,
+    minter: &Address,
+    colors: &Map<Address, Map<u32, Vec<u32>>>,
+) -> Map<Address, Map<u32, u32>> {
+    let mut shortfall: Map<Address, Map<u32, u32>> = Map::new(env);
+
+    for (miner, color_indexes) in colors.iter() {
+        let mut miner_shortfall: Map<u32, u32> = Map::new(env);
+
+        for (color, indexes) in color_indexes.iter() {
+            let current_color_amount = read_color(env, &miner, minter, color);
+
+            if indexes.len() > current_color_amount {
+                miner_shortfall.set(color, indexes.len() - current_color_amount);
+            }
+        }
+
+        if !miner_shortfall.is_empty() {
+            shortfall.set(miner, miner_shortfall);
+        }
+    }
+
+    shortfall
+}
+
+// Spend `from`'s colors into a glyph build
+// When `from` isn't the `minter` the spend is checked against `from`'s color allowances
+pub fn glyph_mint(
+    env: &Env,
+    hash: BytesN<32>,
+    minter: Address,
+    from: Address,
+    to: Option<Address>,
+    colors: Map<Address, Map<u32, Vec<u32>>>,
+    width: Option<u32>,
+) -> Result<(), Error> {
+    if width == Some(0) {
+        return Err(Error::MissingWidth);
+    }
+
+    let mut glyph = read_glyph_or_default(env, &hash);
+
+    // Only mint if the glyph hasn't yet been minted
+    if glyph.length != 0 {
+        return Err(Error::NotEmpty);
+    }
+
+    // Nor while a scrape of it is still handing colors or bids back
+    if glyph.width != 0 && !glyph.colors.is_empty() {
+        return Err(Error::NotPermitted);
+    }
+
+    let mut new_owner = match to.clone() {
+        Some(address) => address,
+        None => minter.clone(),
+    };
+    let mut contributors = read_glyph_contributors(env, &hash);
+    let mut contributing = false;
+    let mut spends = read_glyph_spends(env, &hash);
+
+    // Starting the mint, assign an owner
+    // A scraped glyph keeps its old width which would otherwise read as a scrape in progress
+    if glyph.colors.is_empty() {
+        // An empty build would read as scraped rather than building
+        if colors.is_empty() {
+            return Err(Error::NotPermitted);
+        }
+
+        glyph_require_reserver(env, &hash, &minter)?;
+
+        glyph.width = 0;
+        contributors = Map::new(env);
+        spends = Map::new(env);
+        write_glyph_contributors(env, &hash, &contributors);
+        write_glyph_owner(env, &hash, &new_owner);
+    } else {
+        let existing_owner = read_glyph_owner(env, &hash).ok_or(Error::NotFound)?;
+
+        // Allowlisted contributors add pixels but only the owner can hand off or finish the build
+        if minter != existing_owner && contributors.contains_key(minter.clone()) {
+            if width.is_some() || to.is_some_and(|to| to != existing_owner) {
+                return Err(Error::NotAuthorized);
+            }
+
+            new_owner = existing_owner;
+            contributing = true;
+        } else {
+            // The entry point already has the minter's signature, only another owner needs to sign
+            if existing_owner != minter {
+                glyph_verify_ownership(env, &hash, None);
+            }
+
+            if existing_owner != new_owner {
+                write_glyph_owner(env, &hash, &new_owner);
+            }
+        }
+    }
+
+    let mut pixels: u32 = 0;
+
+    // spend colors
+    for (miner, color_indexes) in colors.iter() {
+        let mut skip = false;
+
+        for (color, indexes) in color_indexes.iter() {
+            if from != minter {
+                color_spend_allowance(env, &from, &minter, &miner, color, indexes.len());
+            }
+
+            pixels += indexes.len();
+
+            let current_color_amount = read_color(env, &miner, &from, color);
+            write_color(
+                env,
+                &miner,
+                &from,
+                color,
+                current_color_amount - indexes.len(),
+            );
+            write_color_locked(env, color, indexes.len());
+
+            let spend = (from.clone(), miner.clone(), color);
+            spends.set(
+                spend.clone(),
+                spends.get(spend).unwrap_or(0) + indexes.len(),
+            );
+
+            // crate::events::colors_out(env, &miner, &from, color, indexes.len());
+
+            if !skip {
+                match glyph.colors.get(miner.clone()) {
+                    Some(result) => match result {
+                        mut color_indexes_ => match color_indexes_.get(color) {
+                            // Existing miner and color
+                            Some(result) => match result {
+                                mut indexes_ => {
+                                    indexes_.append(&indexes);
+                                    color_indexes_.set(color, indexes_);
+                                    glyph.colors.set(miner.clone(), color_indexes_);
+                                }
+                            },
+                            // Existing miner no color
+                            None => {
+                                color_indexes_.set(color, indexes);
+                                glyph.colors.set(miner.clone(), color_indexes_);
+                            }
+                        },
+                    },
+                    // No miner (or no exisiting Colors)
+                    None => {
+                        glyph.colors.set(miner.clone(), color_indexes.clone());
+                        // We set a skip vs using break to ensure we continue to bill for the spent colors
+                        skip = true; // we need to break here otherwise we continue looping inside this nested color loop which we've already fully added
+                    }
+                }
+            }
+        }
+    }
+
+    if contributing {
+        contributors.set(
+            minter.clone(),
+            contributors.get(minter.clone()).unwrap_or(0) + pixels,
+        );
+        write_glyph_contributors(env, &hash, &contributors);
+    }
+
+    // Once minted the colors belong to the glyph, a scrape hands them all to its owner
+    if width.is_some() {
+        spends = Map::new(env);
+    }
+
+    write_glyph_spends(env, &hash, &spends);
+
+    match width {
+        // We are storing the glyph
+        Some(width) => {
+            let first_mint = read_glyph_minter(env, &hash).is_none();
+            let computed_hash = glyph_store(env, glyph.colors, width as u8);
+
+            // println!("HASH: {:?}", computed_hash);
+
+            if hash != computed_hash {
+                return Err(Error::NotPermitted);
+            }
+
+            glyph_mint_record(env, &minter, &new_owner, &hash);
+
+            // Lineage is recorded once per hash, re-mints after a scrape are already listed
+            if first_mint {
+                if let Some(parent) = read_glyph_parent(env, &hash) {
+                    add_glyph_child(env, &parent, &hash);
+                }
+            }
+
+            // crate::events::minted_event(env, &minter, to, &hash);
+        }
+        // We are building the glyph
+        None => {
+            let glyph_key = StorageKey::Glyph(hash.clone());
+
+            env.storage()
+                .persistent()
+                .set::<StorageKey, Glyph>(&glyph_key, &glyph);
+
+            // crate::events::minting_event(env, &minter);
+        }
+    }
+
+    Ok(())
+}
+
+// Allow or disallow `contributor` to add pixels from their own colors to a pending build
+// Anyone who has already placed pixels stays on until the build is cancelled
+pub fn glyph_contributor_set(
+    env: &Env,
+    hash: &BytesN<32>,
+    contributor: Address,
+    allowed: bool,
+) -> Result<(), Error> {
+    match glyph_status(env, hash) {
+        GlyphStatus::Building(_) => {}
+        GlyphStatus::Unknown => return Err(Error::NotFound),
+        _ => return Err(Error::NotPermitted),
+    }
+
+    glyph_verify_ownership(env, hash, None);
+
+    let mut contributors = read_glyph_contributors(env, hash);
+
+    if allowed {
+        if !contributors.contains_key(contributor.clone()) {
+            contributors.set(contributor, 0);
+        }
+    } else {
+        match contributors.get(contributor.clone()) {
+            Some(0) => {
+                contributors.remove(contributor);
+            }
+            Some(_) => return Err(Error::NotEmpty),
+            None => return Err(Error::NotFound),
+        }
+    }
+
+    write_glyph_contributors(env, hash, &contributors);
+
+    Ok(())
+}
+
+// Declare the glyph a pending build derives from, its sales then pay the parent's minter a royalty
+// Parents whose owner has locked derivation also need that owner to sign off
+pub fn glyph_parent_set(env: &Env, hash: &BytesN<32>, parent: &BytesN<32>) -> Result<(), Error> {
+    match glyph_status(env, hash) {
+        GlyphStatus::Building(_) => {}
+        GlyphStatus::Unknown => return Err(Error::NotFound),
+        _ => return Err(Error::NotPermitted),
+    }
+
+    if hash == parent {
+        return Err(Error::NotPermitted);
+    }
+
+    if read_glyph_parent(env, hash).is_some() {
+        return Err(Error::NotEmpty);
+    }
+
+    glyph_require_minted(env, parent)?;
+    glyph_verify_ownership(env, hash, None);
+
+    if read_glyph_derive_locked(env, parent) {
+        read_glyph_owner(env, parent)
+            .ok_or(Error::NotFound)?
+            .require_auth();
+    }
+
+    write_glyph_parent(env, hash, parent);
+
+    Ok(())
+}
+
+// Mint a mirrored, rotated or inverted copy of a minted glyph from `minter`'s own colors
+// The variant is linked to its source as a derivative
+pub fn glyph_mint_variant(
+    env: &Env,
+    minter: Address,
+    to: Option<Address>,
+    source_hash: &BytesN<32>,
+    transform: Transform,
+) -> Result<BytesN<32>, Error> {
+    glyph_require_minted(env, source_hash)?;
+
+    let source = read_glyph(env, source_hash).ok_or(Error::NotFound)?;
+    let width = source.width;
+    let height = source.length.div_ceil(width);
+
+    let new_width = match transform {
+        Transform::Rotate90 | Transform::Rotate270 => height,
+        _ => width,
+    };
+
+    if new_width > u8::MAX as u32 {
+        return Err(Error::NotPermitted);
+    }
+
+    let mut colors: Map<Address, Map<u32, Vec<u32>>> = Map::new(env);
+
+    for (miner, color_indexes) in source.colors.iter() {
+        let mut new_color_indexes: Map<u32, Vec<u32>> = Map::new(env);
+
+        for (color, indexes) in color_indexes.iter() {
+            let mut new_indexes = Vec::new(env);
+
+            for index in indexes.iter() {
+                let (x, y) = (index % width, index / width);
+                let new_index = match transform {
+                    Transform::FlipH => y * width + (width - 1 - x),
+                    Transform::FlipV => (height - 1 - y) * width + x,
+                    Transform::Rotate90 => x * height + (height - 1 - y),
+                    Transform::Rotate180 => (height - 1 - y) * width + (width - 1 - x),
+                    Transform::Rotate270 => (width - 1 - x) * height + y,
+                    Transform::Invert => index,
+                };
+
+                // A short last row can push pixels past the 40x40 bitmap the hash is taken over
+                if new_index >= (MAX_BIT24_SIZE as u32 - 1) / 3 {
+                    return Err(Error::NotPermitted);
+                }
+
+                new_indexes.push_back(new_index);
+            }
+
+            let new_color = match transform {
+                Transform::Invert => color ^ 0xFFFFFF,
+                _ => color,
+            };
+
+            // Spend the colors the variant needs
+            let current_color_amount = read_color(env, &miner, &minter, new_color);
+
+            if new_indexes.len() > current_color_amount {
+                return Err(Error::NotPermitted);
+            }
+
+            write_color(
+                env,
+                &miner,
+                &minter,
+                new_color,
+                current_color_amount - new_indexes.len(),
+            );
+            write_color_locked(env, new_color, new_indexes.len());
+
+            new_color_indexes.set(new_color, new_indexes);
+        }
+
+        colors.set(miner, new_color_indexes);
+    }
+
+    let (hash, _) = glyph_hash(env, &colors, new_width as u8);
+
+    match glyph_status(env, &hash) {
+        GlyphStatus::Unknown | GlyphStatus::Scraped => {}
+        _ => return Err(Error::NotEmpty),
+    }
+
+    glyph_require_reserver(env, &hash, &minter)?;
+
+    if read_glyph_parent(env, &hash).is_none() {
+        if read_glyph_derive_locked(env, source_hash) {
+            read_glyph_owner(env, source_hash)
+                .ok_or(Error::NotFound)?
+                .require_auth();
+        }
+
+        write_glyph_parent(env, &hash, source_hash);
+        add_glyph_child(env, source_hash, &hash);
+    }
+
+    let owner = to.unwrap_or(minter.clone());
+
+    glyph_store(env, colors, new_width as u8);
+    glyph_mint_record(env, &minter, &owner, &hash);
+
+    Ok(hash)
+}
+
+// Swap some of a minted glyph's pixels out for new ones in place of a full scrape and re-mint
+// Removed pixels are credited back to the owner and added pixels are spent from the owner's colors
+// The old hash is retired and the glyph carries on under its new hash with the same owner and minter
+pub fn glyph_repaint(
+    env: &Env,
+    hash: &BytesN<32>,
+    remove: Map<Address, Map<u32, Vec<u32>>>,
+    add: Map<Address, Map<u32, Vec<u32>>>,
+    width: u32,
+) -> Result<BytesN<32>, Error> {
+    if width == 0 {
+        return Err(Error::MissingWidth);
+    }
+
+    glyph_require_minted(env, hash)?;
+
+    // A canvas snapshot's colors are still locked in the canvas
+    if read_canvas_snapshot(env, hash).is_some() {
+        return Err(Error::NotPermitted);
+    }
+
+    let owner = glyph_verify_ownership(env, hash, None);
+    let minter = read_glyph_minter(env, hash).ok_or(Error::NotFound)?;
+    let mut glyph = read_glyph(env, hash).ok_or(Error::NotFound)?;
+
+    for (miner, color_indexes) in remove.iter() {
+        let mut glyph_color_indexes = glyph.colors.get(miner.clone()).ok_or(Error::NotFound)?;
+
+        for (color, indexes) in color_indexes.iter() {
+            let mut glyph_indexes = glyph_color_indexes.get(color).ok_or(Error::NotFound)?;
+
+            for index in indexes.iter() {
+                let position = glyph_indexes.first_index_of(index).ok_or(Error::NotFound)?;
+
+                glyph_indexes.remove(position);
+            }
+
+            if glyph_indexes.is_empty() {
+                glyph_color_indexes.remove(color);
+            } else {
+                glyph_color_indexes.set(color, glyph_indexes);
+            }
+
+            let current_color_amount = read_color(env, &miner, &owner, color);
+            write_color(
+                env,
+                &miner,
+                &owner,
+                color,
+                current_color_amount + indexes.len(),
+            );
+            write_color_unlocked(env, color, indexes.len(), false);
+        }
+
+        if glyph_color_indexes.is_empty() {
+            glyph.colors.remove(miner);
+        } else {
+            glyph.colors.set(miner, glyph_color_indexes);
+        }
+    }
+
+    for (miner, color_indexes) in add.iter() {
+        let mut glyph_color_indexes = glyph.colors.get(miner.clone()).unwrap_or(Map::new(env));
+
+        for (color, indexes) in color_indexes.iter() {
+            let current_color_amount = read_color(env, &miner, &owner, color);
+
+            if indexes.len() > current_color_amount {
+                return Err(Error::NotPermitted);
+            }
+
+            write_color(
+                env,
+                &miner,
+                &owner,
+                color,
+                current_color_amount - indexes.len(),
+            );
+            write_color_locked(env, color, indexes.len());
+
+            let mut glyph_indexes = glyph_color_indexes.get(color).unwrap_or(Vec::new(env));
+
+            glyph_indexes.append(&indexes);
+            glyph_color_indexes.set(color, glyph_indexes);
+        }
+
+        glyph.colors.set(miner, glyph_color_indexes);
+    }
+
+    if glyph.colors.is_empty() {
+        return Err(Error::NotPermitted);
+    }
+
+    let (new_hash, _) = glyph_hash(env, &glyph.colors, width as u8);
+
+    if new_hash == *hash {
+        return Err(Error::NotPermitted);
+    }
+
+    // Don't paint over a glyph someone else has minted or is building
+    match glyph_status(env, &new_hash) {
+        GlyphStatus::Unknown | GlyphStatus::Scraped => {}
+        _ => return Err(Error::NotEmpty),
+    }
+
+    glyph_require_reserver(env, &new_hash, &owner)?;
+
+    // Retire the old hash, any bids left on it show up as stranded
+    remove_owner_glyph(env, &owner, hash);
+    remove_glyph_offer(env, hash);
+    remove_glyph_approval(env, hash);
+    remove_glyph_owner(env, hash);
+    env.storage()
+        .persistent()
+        .remove(&StorageKey::Glyph(hash.clone()));
+
+    glyph_store(env, glyph.colors, width as u8);
+
+    // The new hash may have been minted and scraped by someone else before, it's this minter's now
+    write_glyph_minter(env, &new_hash, &minter);
+    write_glyph_owner(env, &new_hash, &owner);
+    add_owner_glyph(env, &owner, &new_hash);
+
+    write_glyph_contributors(env, &new_hash, &read_glyph_contributors(env, hash));
+    write_glyph_contributors(env, hash, &Map::new(env));
+
+    // A repainted derivative still owes its parent, which lists the new hash in place of the old
+    if let Some(parent) = read_glyph_parent(env, hash) {
+        remove_glyph_child(env, &parent, hash);
+        remove_glyph_parent(env, hash);
+
+        if read_glyph_parent(env, &new_hash).is_none() {
+            write_glyph_parent(env, &new_hash, &parent);
+            add_glyph_child(env, &parent, &new_hash);
+        }
+    }
+
+    events::repaint_glyph_event(env, &owner, hash, &new_hash);
+
+    Ok(new_hash)
+}
+
+// Builds and scrapes both leave `length` at zero, they're told apart by `width`
+// which is only set once `glyph_store` has run and survives the scrape
+// Counting a scrape's remaining calls reads every bid on the glyph, so state changes check the glyph directly
+pub fn glyph_status(env: &Env, hash: &BytesN<32>) -> GlyphStatus {
+    match read_glyph(env, hash) {
+        Some(glyph) => glyph_status_of(env, hash, &glyph),
+        None if read_animation(env, hash).is_some() => GlyphStatus::Animated,
+        None if read_composite(env, hash).is_some() => GlyphStatus::Composite,
+        None => GlyphStatus::Unknown,
+    }
+}
+
+fn glyph_status_of(env: &Env, hash: &BytesN<32>, glyph: &Glyph) -> GlyphStatus {
+    if glyph.length != 0 {
+        return GlyphStatus::Minted;
+    }
+
+    if glyph.colors.is_empty() {
+        return GlyphStatus::Scraped;
+    }
+
+    if glyph.width == 0 {
+        let mut pixels: u32 = 0;
+
+        for (_, color_indexes) in glyph.colors.iter() {
+            for (_, indexes) in color_indexes.iter() {
+                pixels += indexes.len();
+            }
+        }
+
+        return GlyphStatus::Building(pixels);
+    }
+
+    let mut entries: u32 = 0;
+
+    for (_, color_indexes) in glyph.colors.iter() {
+        entries += color_indexes.len();
+    }
+
+    // Escrowed bids are refunded first, the colors go out of whatever budget they leave over
+    let budget = read_max_payment_count(env).max(1);
+    let bids = offers_escrowed(env, hash);
+    let remainder = bids % budget;
+    let mut calls = bids / budget;
+    let mut color_entries = entries;
+
+    if remainder != 0 {
+        calls += 1;
+        color_entries = color_entries.saturating_sub((budget - remainder) / COLOR_PAYMENT_ENTRIES);
+    }
+
+    let payments_per_call = (budget / COLOR_PAYMENT_ENTRIES).max(1);
+
+    GlyphStatus::Scraping(entries, calls + color_entries.div_ceil(payments_per_call))
+}
+
+// Reserve a glyph without giving away its hash, `commitment` is sha256(hash ++ salt)
+pub fn glyph_reserve(
+    env: &Env,
+    reserver: &Address,
+    commitment: &BytesN<32>,
+    live_until_ledger: u32,
+) -> Result<(), Error> {
+    let sequence = env.ledger().sequence();
+
+    if live_until_ledger < sequence || live_until_ledger > sequence + MAX_RESERVATION_LEDGERS {
+        return Err(Error::NotPermitted);
+    }
+
+    write_reservation_commitment(env, reserver, commitment, live_until_ledger);
+
+    Ok(())
+}
+
+// Open a commitment, locking its hash to the reserver until the commitment would have expired
+// Revealing before the first `glyph_mint` means the pixel data only goes on chain once the hash is locked
+// Commitments can't be opened in the ledger they were made in, so a hash seen in a pending reveal can't be
+// committed to and revealed ahead of it
+pub fn glyph_reveal(
+    env: &Env,
+    reserver: &Address,
+    hash: &BytesN<32>,
+    salt: &BytesN<32>,
+) -> Result<(), Error> {
+    let mut preimage: Bytes = hash.clone().into();
+
+    preimage.append(&salt.clone().into());
+
+    let commitment = env.crypto().sha256(&preimage).to_bytes();
+    let (committed_ledger, live_until_ledger) =
+        read_reservation_commitment(env, reserver, &commitment).ok_or(Error::NotFound)?;
+
+    if committed_ledger >= env.ledger().sequence() {
+        return Err(Error::NotPermitted);
+    }
+
+    match glyph_status(env, hash) {
+        GlyphStatus::Unknown | GlyphStatus::Scraped => {}
+        GlyphStatus::Building(_) if read_glyph_owner(env, hash) == Some(reserver.clone()) => {}
+        _ => return Err(Error::NotEmpty),
+    }
+
+    glyph_require_reserver(env, hash, reserver)?;
+
+    remove_reservation_commitment(env, reserver, &commitment);
+    write_glyph_reservation(
+        env,
+        hash,
+        &GlyphReservation {
+            reserver: reserver.clone(),
+            live_until_ledger,
+        },
+    );
+
+    Ok(())
+}
+
+// A live reservation keeps everyone but its reserver from starting the glyph
+pub fn glyph_require_reserver(env: &Env, hash: &BytesN<32>, minter: &Address) -> Result<(), Error> {
+    match read_glyph_reservation(env, hash) {
+        Some(reserver) if reserver != *minter => Err(Error::Reserved),
+        _ => Ok(()),
+    }
+}
+
+// Hand a pending build's colors back to whoever spent them, one `MaxPaymentCount` worth of writes per call
+// Colors from builds that predate spend tracking go to the owner
+// Once nothing is left the build and its owner are cleared so the hash can be minted fresh
+pub fn glyph_mint_cancel(env: &Env, hash: &BytesN<32>) -> Result<(), Error> {
+    let owner = glyph_verify_ownership(env, hash, None);
+    let mut glyph = read_glyph(env, hash).ok_or(Error::NotFound)?;
+
+    // Only builds can be cancelled, minted glyphs are scraped instead
+    if glyph.length != 0 {
+        return Err(Error::NotEmpty);
+    }
+
+    if glyph.width != 0 || glyph.colors.is_empty() {
+        return Err(Error::NotPermitted);
+    }
+
+    let budget = read_max_payment_count(env);
+    let mut entry_count: u32 = 0;
+    let mut spends = read_glyph_spends(env, hash);
+
+    for ((spender, miner, color), amount) in spends.iter() {
+        if entry_count > 0 && entry_count + COLOR_PAYMENT_ENTRIES > budget {
+            break;
+        }
+
+        let mut colors_indexes = glyph.colors.get(miner.clone()).unwrap_or(Map::new(env));
+        let indexes = colors_indexes.get(color).unwrap_or(Vec::new(env));
+        let refund = amount.min(indexes.len());
+        let current_amount = read_color(env, &miner, &spender, color);
+
+        write_color(env, &miner, &spender, color, current_amount + refund);
+        write_color_unlocked(env, color, refund, false);
+
+        if refund == indexes.len() {
+            colors_indexes.remove(color);
+        } else {
+            colors_indexes.set(color, indexes.slice(refund..));
+        }
+
+        if colors_indexes.is_empty() {
+            glyph.colors.remove(miner.clone());
+        } else {
+            glyph.colors.set(miner.clone(), colors_indexes);
+        }
+
+        spends.remove((spender, miner, color));
+        entry_count += COLOR_PAYMENT_ENTRIES;
+    }
+
+    write_glyph_spends(env, hash, &spends);
+
+    if spends.is_empty() && (entry_count == 0 || entry_count + COLOR_PAYMENT_ENTRIES <= budget) {
+        glyph_colors_return(env, &mut glyph.colors, &owner, budget - entry_count);
+    }
+
+    let glyph_key = StorageKey::Glyph(hash.clone());
+
+    if glyph.colors.is_empty() {
+        env.storage().persistent().remove(&glyph_key);
+        remove_glyph_owner(env, hash);
+        remove_glyph_approval(env, hash);
+        write_glyph_contributors(env, hash, &Map::new(env));
+        write_glyph_spends(env, hash, &Map::new(env));
+
+        // A parent declared for a build that never minted goes with it
+        if read_glyph_minter(env, hash).is_none() {
+            remove_glyph_parent(env, hash);
+        }
+    } else {
+        env.storage()
+            .persistent()
+            .set::<StorageKey, Glyph>(&glyph_key, &glyph);
+    }
+
+    Ok(())
+}
+
+// Move miner/color entries out of `colors` and into `to`'s balances, each payment writing up to
+// `COLOR_PAYMENT_ENTRIES` ledger entries out of a `budget` (at least one payment is always made)
+pub fn glyph_colors_return(
+    env: &Env,
+    colors: &mut Map<Address, Map<u32, Vec<u32>>>,
+    to: &Address,
+    budget: u32,
+) {
+    let max_payment_count = (budget / COLOR_PAYMENT_ENTRIES).max(1);
+    let mut payment_count: u32 = 0;
+
+    for (miner, mut colors_indexes) in colors.iter() {
+        for (color, indexes) in colors_indexes.iter() {
+            let current_amount = read_color(env, &miner, to, color);
+
+            write_color(env, &miner, to, color, current_amount + indexes.len());
+            write_color_unlocked(env, color, indexes.len(), false);
+
+            colors_indexes.remove(color);
+            payment_count += 1;
+
+            if payment_count >= max_payment_count {
+                break;
+            }
+
+            // crate::events::color_in_event(env, &miner, to, color, indexes.len());
+        }
+
+        if colors_indexes.is_empty() {
+            colors.remove(miner);
+        } else {
+            colors.set(miner, colors_indexes);
+        }
+
+        if payment_count >= max_payment_count {
+            break;
+        }
+    }
+}
+
+pub fn glyph_store(env: &Env, colors: Map<Address, Map<u32, Vec<u32>>>, width: u8) -> BytesN<32> {
+    let (hash, length) = glyph_hash(env, &colors, width);
+
+    // Once stored the glyph is taken so any reservation on it has done its job
+    remove_glyph_reservation(env, &hash);
+
+    // Save the glyph to storage
+    let glyph_key = StorageKey::Glyph(hash.clone());
+
+    env.storage().persistent().set::<StorageKey, Glyph>(
+        &glyph_key,
+        &Glyph {
+            width: width as u32,
+            length,
+            colors,
+        },
+    );
+
+    hash
+}
+
+// Compute the hash and pixel length of a glyph without touching storage
+pub fn glyph_hash(
+    env: &Env,
+    colors: &Map<Address, Map<u32, Vec<u32>>>,
     width: u8,
-) -> BytesN<32> {
+) -> (BytesN<32>, u32) {
     let mut max_i = 0;
     let mut bit24_data = [u8::MAX; MAX_BIT24_SIZE];
 
@@ -44,36 +937,32 @@ pub fn glyph_store(
 
     // println!("HASH: {:?}", hash);
 
-    // Save the glyph minter to storage (if glyph hasn't already been minted)
-    let glyph_minter_key = StorageKey::GlyphMinter(hash.clone());
-
-    if !env.storage().persistent().has(&glyph_minter_key) {
-        env.storage().persistent().set(&glyph_minter_key, &minter);
-    }
-
-    // Save the glyph to storage
-    let glyph_key = StorageKey::Glyph(hash.clone());
-
-    env.storage().persistent().set::<StorageKey, Glyph>(
-        &glyph_key,
-        &Glyph {
-            width: width as u32,
-            length: (bytes.len() - 1) / 3, // remove one byte for the length and divide by 3 for the RGB
-            colors,
-        },
-    );
-
-    hash
+    // remove one byte for the length and divide by 3 for the RGB
+    (hash, (bytes.len() - 1) / 3)
 }
 
-pub fn glyph_verify_ownership(env: &Env, glyph_owner_key: &StorageKey) -> Address {
+// Returns the glyph owner once either the owner or the given `spender` has authorized
+// A spender other than the owner must be approved for the glyph or be one of the owner's operators
+pub fn glyph_verify_ownership(env: &Env, hash: &BytesN<32>, spender: Option<Address>) -> Address {
+    let glyph_owner_key = StorageKey::GlyphOwner(hash.clone());
     let glyph_owner = env
         .storage()
         .persistent()
-        .get::<StorageKey, Address>(glyph_owner_key)
+        .get::<StorageKey, Address>(&glyph_owner_key)
         .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
 
-    glyph_owner.require_auth();
+    match spender {
+        Some(spender) if spender != glyph_owner => {
+            if read_glyph_approval(env, hash) != Some(spender.clone())
+                && !read_glyph_operator(env, &glyph_owner, &spender)
+            {
+                panic_with_error!(env, Error::NotAuthorized);
+            }
+
+            spender.require_auth();
+        }
+        _ => glyph_owner.require_auth(),
+    }
 
     // env.storage()
     //     .persistent()
@@ -81,3 +970,144 @@ pub fn glyph_verify_ownership(env: &Env, glyph_owner_key: &StorageKey) -> Addres
 
     glyph_owner
 }
+
+// Move a glyph from `from` to `to`, closing out any sell offers `from` had open for it
+pub fn glyph_transfer(
+    env: &Env,
+    spender: &Address,
+    from: &Address,
+    to: &Address,
+    hash: &BytesN<32>,
+) -> Result<(), Error> {
+    let owner = glyph_verify_ownership(env, hash, Some(spender.clone()));
+
+    glyph_move(env, &owner, from, to, hash)
+}
+
+// Shared by every transfer path once the caller has been authorized
+pub fn glyph_move(
+    env: &Env,
+    owner: &Address,
+    from: &Address,
+    to: &Address,
+    hash: &BytesN<32>,
+) -> Result<(), Error> {
+    if owner != from {
+        return Err(Error::NotAuthorized);
+    }
+
+    if from == to {
+        return Err(Error::NotPermitted);
+    }
+
+    glyph_require_minted(env, hash)?;
+
+    write_glyph_owner(env, hash, to);
+    remove_glyph_offer(env, hash);
+
+    events::transfer_glyph_event(env, from, to, hash);
+
+    Ok(())
+}
+
+// Partially minted and scraped glyphs can't change hands
+// A glyph only carries a length once `glyph_store` has run for it, and scraping zeroes it again
+pub fn glyph_require_minted(env: &Env, hash: &BytesN<32>) -> Result<(), Error> {
+    match read_glyph(env, hash) {
+        Some(glyph) if glyph.length != 0 => Ok(()),
+        None if read_animation(env, hash).is_some() || read_composite(env, hash).is_some() => {
+            Ok(())
+        }
+        _ => Err(Error::NotMinted),
+    }
+}
+
+// Hand plain minted glyphs `owner` holds to the contract, e.g. as the frames or layers of a bundle
+// Everything is checked before anything moves so a bad hash leaves the rest untouched
+pub fn glyph_escrow(env: &Env, owner: &Address, hashes: &Vec<BytesN<32>>) -> Result<(), Error> {
+    for hash in hashes.iter() {
+        // Bundles can't nest
+        if read_glyph(env, &hash).is_none() {
+            return Err(Error::NotPermitted);
+        }
+
+        glyph_require_minted(env, &hash)?;
+
+        if read_glyph_owner(env, &hash) != Some(owner.clone()) {
+            return Err(Error::NotAuthorized);
+        }
+    }
+
+    for hash in hashes.iter() {
+        remove_glyph_offer(env, &hash);
+        write_glyph_owner(env, &hash, &env.current_contract_address());
+    }
+
+    Ok(())
+}
+
+pub fn glyph_escrow_release(env: &Env, hashes: &Vec<BytesN<32>>, to: &Address) {
+    for hash in hashes.iter() {
+        if read_glyph_owner(env, &hash) == Some(env.current_contract_address()) {
+            write_glyph_owner(env, &hash, to);
+        }
+    }
+}
+
+// Record a freshly minted glyph under its minter (if it hasn't been minted before) and owner
+pub fn glyph_mint_record(env: &Env, minter: &Address, owner: &Address, hash: &BytesN<32>) {
+    glyph_bundle_record(env, minter, owner, hash);
+
+    let mut stats = read_stats(env);
+    stats.glyphs_minted += 1;
+    write_stats(env, &stats);
+}
+
+// Bundles are recorded like any other mint but aren't counted as minted, their frames and layers already are
+pub fn glyph_bundle_record(env: &Env, minter: &Address, owner: &Address, hash: &BytesN<32>) {
+    if read_glyph_minter(env, hash).is_none() {
+        write_glyph_minter(env, hash, minter);
+    }
+
+    write_glyph_owner(env, hash, owner);
+    add_owner_glyph(env, owner, hash);
+}
+
+pub fn glyph_bundle_remove(env: &Env, owner: &Address, hash: &BytesN<32>) {
+    remove_owner_glyph(env, owner, hash);
+    remove_glyph_offer(env, hash);
+    remove_glyph_approval(env, hash);
+    remove_glyph_owner(env, hash);
+}
+
+// Like `glyph_transfer` but contract recipients must accept the glyph via `on_glyph_received`
+pub fn glyph_safe_transfer(
+    env: &Env,
+    spender: &Address,
+    from: &Address,
+    to: &Address,
+    hash: &BytesN<32>,
+    data: &Bytes,
+) -> Result<(), Error> {
+    glyph_transfer(env, spender, from, to, hash)?;
+
+    // A refusal errors out of the call, which rolls the transfer back
+    if is_contract(to) {
+        let receiver = GlyphReceiverClient::new(env, to);
+
+        if !receiver.on_glyph_received(spender, from, hash, data) {
+            return Err(Error::NotPermitted);
+        }
+    }
+
+    Ok(())
+}
+
+// Contract strkeys start with a C, account strkeys with a G
+fn is_contract(address: &Address) -> bool {
+    let mut strkey = [0u8; 56];
+
+    address.to_string().copy_into_slice(&mut strkey);
+
+    strkey[0] == b'C'
+}
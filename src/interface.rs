@@ -1,6 +1,9 @@
-use soroban_sdk::{Address, BytesN, Env, Map, Vec};
+use soroban_sdk::{contractclient, Address, Bytes, BytesN, Env, Map, String, Vec};
 
-use crate::types::{Error, Glyph, Offer};
+use crate::types::{
+    Bitmap, Canvas, CanvasPixel, ColorAllowance, ColorSupply, Error, Glyph, GlyphStatus, Layer,
+    MinePricing, MintQuote, Offer, Stats, Transform,
+};
 
 pub trait ColorGlyphTrait {
     fn initialize(
@@ -21,7 +24,12 @@ pub trait ColorGlyphTrait {
         minter_royalty_rate: Option<i128>,
         miner_royalty_rate: Option<i128>,
     );
+    fn base_uri_set(env: Env, base_uri: String) -> Result<(), Error>;
+    fn derivative_royalty_rate_set(env: Env, rate: i128) -> Result<(), Error>;
+    fn mine_pricing_set(env: Env, mine_pricing: Option<MinePricing>) -> Result<(), Error>;
+    fn mine_pricing(env: Env) -> Option<MinePricing>;
     fn upgrade(env: Env, hash: BytesN<32>);
+    fn stats(env: Env) -> Stats;
 }
 
 pub trait ColorsInterface {
@@ -31,9 +39,40 @@ pub trait ColorsInterface {
         colors: Map<u32, u32>,
         miner: Option<Address>,
         to: Option<Address>,
-    );
+        max_cost: Option<i128>,
+    ) -> Result<i128, Error>;
+    fn colors_mine_quote(env: Env, colors: Map<u32, u32>) -> i128;
     fn colors_transfer(env: Env, from: Address, to: Address, colors: Vec<(Address, u32, u32)>);
+    fn colors_transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        colors: Vec<(Address, u32, u32)>,
+    );
+    fn colors_approve(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        miner: Address,
+        color: u32,
+        amount: u32,
+        live_until_ledger: u32,
+    );
+    fn colors_allowance(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        miner: Address,
+        color: u32,
+    ) -> ColorAllowance;
+    fn colors_set_operator(env: Env, owner: Address, operator: Address, live_until_ledger: u32);
+    fn colors_is_operator(env: Env, owner: Address, operator: Address) -> bool;
     fn color_balance(env: Env, owner: Address, color: u32, miner: Option<Address>) -> u32;
+    fn color_balances(env: Env, owner: Address, colors: Vec<(Option<Address>, u32)>) -> Vec<u32>;
+    fn color_balance_total(env: Env, owner: Address, color: u32) -> u32;
+    fn color_supply(env: Env, color: u32) -> ColorSupply;
+    fn colors_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<(Address, u32, u32)>;
 }
 
 pub trait GlyphInterface {
@@ -44,14 +83,185 @@ pub trait GlyphInterface {
         to: Option<Address>,
         colors: Map<Address, Map<u32, Vec<u32>>>,
         width: Option<u32>,
-    );
-    fn glyph_transfer(env: Env, to: Address, hash: BytesN<32>);
-    fn glyph_scrape(env: Env, to: Option<Address>, hash: BytesN<32>);
+    ) -> Result<(), Error>;
+    fn glyph_mint_from(
+        env: Env,
+        hash: BytesN<32>,
+        spender: Address,
+        from: Address,
+        to: Option<Address>,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: Option<u32>,
+    ) -> Result<(), Error>;
+    fn glyph_mine_and_mint(
+        env: Env,
+        hash: BytesN<32>,
+        minter: Address,
+        to: Option<Address>,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: Option<u32>,
+        max_cost: Option<i128>,
+    ) -> Result<i128, Error>;
+    fn glyph_mint_quote(
+        env: Env,
+        minter: Address,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: u32,
+    ) -> Result<MintQuote, Error>;
+    fn glyph_transfer(env: Env, from: Address, to: Address, hash: BytesN<32>) -> Result<(), Error>;
+    fn glyph_scrape(env: Env, to: Option<Address>, hash: BytesN<32>) -> Result<(), Error>;
+    fn glyph_mint_cancel(env: Env, hash: BytesN<32>) -> Result<(), Error>;
+    fn glyph_reserve(
+        env: Env,
+        reserver: Address,
+        commitment: BytesN<32>,
+        live_until_ledger: u32,
+    ) -> Result<(), Error>;
+    fn glyph_reveal(
+        env: Env,
+        reserver: Address,
+        hash: BytesN<32>,
+        salt: BytesN<32>,
+    ) -> Result<(), Error>;
+    fn glyph_reservation(env: Env, hash: BytesN<32>) -> Option<Address>;
+    fn glyph_mint_variant(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        source_hash: BytesN<32>,
+        transform: Transform,
+    ) -> Result<BytesN<32>, Error>;
+    fn glyph_animate(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        frames: Vec<(BytesN<32>, u32)>,
+    ) -> Result<BytesN<32>, Error>;
+    fn glyph_unanimate(env: Env, hash: BytesN<32>) -> Result<(), Error>;
+    fn glyph_animation(env: Env, hash: BytesN<32>) -> Result<Vec<(BytesN<32>, u32)>, Error>;
+    fn glyph_animation_svg(env: Env, hash: BytesN<32>) -> Result<Bytes, Error>;
+    fn glyph_compose(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        layers: Vec<Layer>,
+    ) -> Result<BytesN<32>, Error>;
+    fn glyph_decompose(env: Env, hash: BytesN<32>) -> Result<(), Error>;
+    fn glyph_composite(env: Env, hash: BytesN<32>) -> Result<Vec<Layer>, Error>;
+    fn glyph_composite_bitmap(env: Env, hash: BytesN<32>) -> Result<Bitmap, Error>;
+    fn glyph_contributor_set(
+        env: Env,
+        hash: BytesN<32>,
+        contributor: Address,
+        allowed: bool,
+    ) -> Result<(), Error>;
+    fn glyph_contributors(env: Env, hash: BytesN<32>) -> Map<Address, u32>;
+    fn glyph_parent_set(env: Env, hash: BytesN<32>, parent: BytesN<32>) -> Result<(), Error>;
+    fn glyph_derive_lock(env: Env, parent: BytesN<32>, locked: bool);
+    fn glyph_parent(env: Env, hash: BytesN<32>) -> Option<BytesN<32>>;
+    fn glyph_children(env: Env, hash: BytesN<32>, cursor: u32, limit: u32) -> Vec<BytesN<32>>;
+    fn glyph_repaint(
+        env: Env,
+        hash: BytesN<32>,
+        remove: Map<Address, Map<u32, Vec<u32>>>,
+        add: Map<Address, Map<u32, Vec<u32>>>,
+        width: u32,
+    ) -> Result<BytesN<32>, Error>;
     fn glyph_get(env: Env, hash: BytesN<32>) -> Result<Glyph, Error>;
+    fn glyph_status(env: Env, hash: BytesN<32>) -> GlyphStatus;
+    fn glyphs_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<BytesN<32>>;
+    fn glyph_count_of(env: Env, owner: Address) -> u32;
+}
+
+// Shared grids where each pixel belongs to whoever painted it last
+pub trait CanvasInterface {
+    fn canvas_create(
+        env: Env,
+        owner: Address,
+        width: u32,
+        height: u32,
+        burn: bool,
+    ) -> Result<u32, Error>;
+    fn canvas_paint(
+        env: Env,
+        painter: Address,
+        id: u32,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+    ) -> Result<(), Error>;
+    fn canvas_get(env: Env, id: u32) -> Result<Canvas, Error>;
+    fn canvas_pixels(
+        env: Env,
+        id: u32,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<Vec<(u32, CanvasPixel)>, Error>;
+    fn canvas_freeze(env: Env, id: u32, to: Option<Address>) -> Result<BytesN<32>, Error>;
 }
 
 pub trait Exchange {
-    fn offer_post(env: Env, sell: Offer, buy: Offer) -> Result<(), Error>;
-    fn offer_delete(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error>;
+    fn offer_post(env: Env, sell: Offer, buy: Offer, spender: Option<Address>)
+        -> Result<(), Error>;
+    fn offer_delete(
+        env: Env,
+        sell: Offer,
+        buy: Option<Offer>,
+        spender: Option<Address>,
+    ) -> Result<(), Error>;
     fn offers_get(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error>;
+    fn offers_stranded(env: Env, hash: BytesN<32>) -> Vec<Offer>;
+    fn offers_reindex(env: Env, hash: BytesN<32>, keys: Vec<(Address, i128)>);
+}
+
+// Glyph hashes double as the token ids
+pub trait NonFungibleToken {
+    fn balance(env: Env, owner: Address) -> u32;
+    fn owner_of(env: Env, hash: BytesN<32>) -> Result<Address, Error>;
+    fn transfer(env: Env, from: Address, to: Address, hash: BytesN<32>) -> Result<(), Error>;
+    fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        hash: BytesN<32>,
+    ) -> Result<(), Error>;
+    fn safe_transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        hash: BytesN<32>,
+        data: Bytes,
+    ) -> Result<(), Error>;
+    fn safe_transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        hash: BytesN<32>,
+        data: Bytes,
+    ) -> Result<(), Error>;
+    fn approve(
+        env: Env,
+        owner: Address,
+        approved: Address,
+        hash: BytesN<32>,
+        live_until_ledger: u32,
+    ) -> Result<(), Error>;
+    fn get_approved(env: Env, hash: BytesN<32>) -> Option<Address>;
+    fn set_approval_for_all(env: Env, owner: Address, operator: Address, live_until_ledger: u32);
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool;
+    fn name(env: Env) -> String;
+    fn symbol(env: Env) -> String;
+    fn token_uri(env: Env, hash: BytesN<32>) -> Result<String, Error>;
+}
+
+// Implemented by contracts which want to be told when a glyph is safely transferred to them
+#[contractclient(name = "GlyphReceiverClient")]
+pub trait GlyphReceiver {
+    fn on_glyph_received(
+        env: Env,
+        operator: Address,
+        from: Address,
+        hash: BytesN<32>,
+        data: Bytes,
+    ) -> bool;
 }
@@ -14,17 +14,27 @@ Ensure fully verifying necessary ownerships
 // NOTE 20 storage writes is very limiting atm
 
 mod contract;
-// mod events;
+mod events;
 mod interface;
 mod storage;
 pub mod types;
 
+// Contracts receiving glyphs via `safe_transfer` implement this
+pub use interface::{GlyphReceiver, GlyphReceiverClient};
+
+mod animations;
+mod canvas;
+mod colors;
+mod composites;
 mod glyphs;
 mod offers;
 
 // #[path = "./tests/misc.rs"]
 // mod misc_test;
 
+#[path = "./tests/canvas.rs"]
+mod canvas_test;
+
 #[path = "./tests/colors.rs"]
 mod colors_test;
 
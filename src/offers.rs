@@ -6,14 +6,17 @@ use soroban_sdk::{
 
 use crate::{
     // events,
-    glyphs::glyph_verify_ownership,
+    glyphs::{glyph_require_minted, glyph_verify_ownership},
     storage::{
-        instance::{read_miner_royalty_rate, read_minter_royalty_rate},
+        instance::{
+            read_derivative_royalty_rate, read_miner_royalty_rate, read_minter_royalty_rate,
+        },
         persistent::{
-            has_asset_offers_by_asset, read_asset_offers_by_asset, read_glyph_minter,
-            read_glyph_or_error, read_glyph_owner, read_offers_by_glyph,
-            remove_asset_offers_by_asset, remove_glyph_offer, write_asset_offers_by_asset,
-            write_glyph_owner, write_offers_by_glyph,
+            has_asset_offers_by_asset, index_asset_offers_by_asset, read_asset_offer_keys,
+            read_asset_offers_by_asset, read_glyph_contributors, read_glyph_minter,
+            read_glyph_or_default, read_glyph_owner, read_glyph_parent, read_offers_by_glyph,
+            read_stats, remove_asset_offers_by_asset, remove_glyph_offer,
+            write_asset_offers_by_asset, write_glyph_owner, write_offers_by_glyph, write_stats,
         },
     },
     types::{Error, Offer, OfferCreate, StorageKey},
@@ -32,13 +35,17 @@ Place caps on the number of GlyphOffer and AssetOffer Vec lengths
     how many sell offers can a Glyph owner open?
     how many identical glyph:asset:amount offers can be open?
 
-!! Ensure we can't sell a partially minted glyph
-    Or at the very least ensure we can't sell a glyph with a color length of zero
-
 !! The way the indexer is written atm we should ensure no two offers are identical
 */
 
-pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
+// When set, `spender` acts on the glyph owner's behalf
+// and must either be approved for the glyph or be one of the owner's operators
+pub fn offer_post(
+    env: &Env,
+    sell: Offer,
+    buy: Offer,
+    spender: Option<Address>,
+) -> Result<(), Error> {
     // sell glyph
     // lookup if someone is selling what you're buying
     // sell asset
@@ -61,21 +68,25 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                     let buy_glyph_owner_address =
                         read_glyph_owner(env, buy_glyph_hash).ok_or(Error::NotFound)?;
 
+                    glyph_require_minted(env, buy_glyph_hash)?;
+
                     offers.remove(offer_index);
 
                     write_offers_by_glyph(&env, buy_glyph_hash, offers);
 
                     match &sell {
                         Offer::Glyph(sell_glyph_hash) => {
-                            let sell_glyph_owner_key =
-                                StorageKey::GlyphOwner(sell_glyph_hash.clone());
+                            glyph_require_minted(env, sell_glyph_hash)?;
+
                             let sell_glyph_owner_address =
-                                glyph_verify_ownership(env, &sell_glyph_owner_key);
+                                glyph_verify_ownership(env, sell_glyph_hash, spender);
 
                             transfer_ownership(env, sell_glyph_hash, &buy_glyph_owner_address);
 
                             transfer_ownership(env, buy_glyph_hash, &sell_glyph_owner_address);
 
+                            record_sale(env);
+
                             // events::offer_match(
                             //     env,
                             //     sell_glyph_hash,
@@ -104,6 +115,8 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                             // remove all other sell offers for this glyph
                             remove_glyph_offer(env, buy_glyph_hash);
 
+                            record_sale(env);
+
                             // events::offer_match_sell_asset(
                             //     env,
                             //     sell_asset_address,
@@ -118,9 +131,11 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                     }
                 }
                 _ => match &sell {
-                    Offer::Glyph(sell_glyph_hash) => {
-                        offer_post_create(env, OfferCreate::Glyph(sell_glyph_hash.clone(), buy))
-                    }
+                    Offer::Glyph(sell_glyph_hash) => offer_post_create(
+                        env,
+                        OfferCreate::Glyph(sell_glyph_hash.clone(), buy),
+                        spender,
+                    ),
                     Offer::AssetSell(sell_asset_owner_address, sell_asset_address, amount) => {
                         offer_post_create(
                             env,
@@ -130,6 +145,7 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                                 sell_asset_address.clone(),
                                 *amount,
                             ),
+                            spender,
                         )
                     }
                     _ => Err(Error::NotPermitted),
@@ -152,12 +168,14 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                         return offer_post_create(
                             env,
                             OfferCreate::Glyph(sell_glyph_hash.clone(), buy),
+                            spender,
                         );
                     }
 
-                    let sell_glyph_owner_key = StorageKey::GlyphOwner(sell_glyph_hash.clone());
+                    glyph_require_minted(env, sell_glyph_hash)?;
+
                     let sell_glyph_owner_address =
-                        glyph_verify_ownership(env, &sell_glyph_owner_key);
+                        glyph_verify_ownership(env, sell_glyph_hash, spender);
 
                     reward_minter_and_miners(
                         env,
@@ -194,6 +212,8 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                     // Remove all other sell offers for this glyph
                     remove_glyph_offer(env, sell_glyph_hash);
 
+                    record_sale(env);
+
                     // events::asset_offer_post(
                     //     env,
                     //     &buy_asset_address,
@@ -211,11 +231,12 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
     }
 }
 
-fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
+fn offer_post_create(env: &Env, offer: OfferCreate, spender: Option<Address>) -> Result<(), Error> {
     match offer {
         OfferCreate::Glyph(sell_glyph_hash, buy) => {
-            let sell_glyph_owner_key = StorageKey::GlyphOwner(sell_glyph_hash.clone());
-            let sell_glyph_owner_address = glyph_verify_ownership(env, &sell_glyph_owner_key);
+            glyph_require_minted(env, &sell_glyph_hash)?;
+
+            let sell_glyph_owner_address = glyph_verify_ownership(env, &sell_glyph_hash, spender);
 
             // Selling a Glyph
             let mut offers = read_offers_by_glyph(env, &sell_glyph_hash);
@@ -228,7 +249,7 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
             write_offers_by_glyph(env, &sell_glyph_hash, offers);
 
             // events::glyph_offer_post(env, &sell_glyph_hash, &sell_glyph_owner_address, buy);
-            
+
             Ok(())
         }
         OfferCreate::Asset(
@@ -269,12 +290,16 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
     }
 }
 
-pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error> {
+pub fn offer_delete(
+    env: &Env,
+    sell: Offer,
+    buy: Option<Offer>,
+    spender: Option<Address>,
+) -> Result<(), Error> {
     match sell {
         Offer::Glyph(glyph_hash) => {
             // Selling a Glyph (delete Glyph or Asset buy offer)
-            let glyph_owner_key = StorageKey::GlyphOwner(glyph_hash.clone());
-            let glyph_owner = glyph_verify_ownership(env, &glyph_owner_key);
+            let glyph_owner = glyph_verify_ownership(env, &glyph_hash, spender);
 
             let mut offers = read_offers_by_glyph(env, &glyph_hash);
 
@@ -413,11 +438,96 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
     }
 }
 
+// Refund up to `budget` escrowed bids on a glyph, returning how many payments were made
+// Bids beyond the budget stay open and can be reclaimed by their owners through `offer_delete`
+pub fn offers_refund(env: &Env, hash: &BytesN<32>, budget: u32) -> u32 {
+    let mut payment_count: u32 = 0;
+
+    for (asset_address, amount) in read_asset_offer_keys(env, hash).iter() {
+        if payment_count >= budget {
+            break;
+        }
+
+        let mut offers =
+            read_asset_offers_by_asset(env, hash, &asset_address, amount).unwrap_or(Vec::new(env));
+        let token = token::Client::new(env, &asset_address);
+
+        while payment_count < budget {
+            match offers.pop_front() {
+                Some(asset_owner_address) => {
+                    token.transfer(
+                        &env.current_contract_address(),
+                        &asset_owner_address,
+                        &amount,
+                    );
+
+                    payment_count += 1;
+                }
+                None => break,
+            }
+        }
+
+        if offers.is_empty() {
+            remove_asset_offers_by_asset(env, hash, &asset_address, amount);
+        } else {
+            write_asset_offers_by_asset(env, hash, &asset_address, amount, &offers);
+        }
+    }
+
+    payment_count
+}
+
+// How many bids are escrowed against a glyph, each one is a refund payment when it's scraped
+pub fn offers_escrowed(env: &Env, hash: &BytesN<32>) -> u32 {
+    let mut count: u32 = 0;
+
+    for (asset_address, amount) in read_asset_offer_keys(env, hash).iter() {
+        if let Some(offers) = read_asset_offers_by_asset(env, hash, &asset_address, amount) {
+            count += offers.len();
+        }
+    }
+
+    count
+}
+
+// Bids posted before `AssetOfferKeys` existed aren't listed under their glyph, so they can't be refunded or
+// found by `offers_stranded` until their (sac, amount) pairs are indexed here. Pairs without bids are skipped
+pub fn offers_reindex(env: &Env, hash: &BytesN<32>, keys: &Vec<(Address, i128)>) {
+    for (asset_address, amount) in keys.iter() {
+        if has_asset_offers_by_asset(env, hash, &asset_address, amount) {
+            index_asset_offers_by_asset(env, hash, &asset_address, amount);
+        }
+    }
+}
+
+// Bids still escrowed against a glyph that is no longer (or not yet) minted
+pub fn offers_stranded(env: &Env, hash: &BytesN<32>) -> Vec<Offer> {
+    let mut stranded = Vec::new(env);
+
+    if glyph_require_minted(env, hash).is_ok() {
+        return stranded;
+    }
+
+    for (asset_address, amount) in read_asset_offer_keys(env, hash).iter() {
+        let offers =
+            read_asset_offers_by_asset(env, hash, &asset_address, amount).unwrap_or(Vec::new(env));
+
+        for asset_owner_address in offers.iter() {
+            stranded.push_back(Offer::AssetSell(
+                asset_owner_address,
+                asset_address.clone(),
+                amount,
+            ));
+        }
+    }
+
+    stranded
+}
+
 fn transfer_ownership(env: &Env, hash: &BytesN<32>, new_owner: &Address) {
-    let glyph_owner_key = StorageKey::GlyphOwner(hash.clone());
     let glyph_offer_key = StorageKey::GlyphOffer(hash.clone());
 
-    env.storage().persistent().set(&glyph_owner_key, &new_owner);
+    write_glyph_owner(env, hash, new_owner);
 
     if env.storage().persistent().has(&glyph_offer_key) {
         // TODO emit offer remove event?
@@ -425,6 +535,12 @@ fn transfer_ownership(env: &Env, hash: &BytesN<32>, new_owner: &Address) {
     }
 }
 
+fn record_sale(env: &Env) {
+    let mut stats = read_stats(env);
+    stats.sales += 1;
+    write_stats(env, &stats);
+}
+
 fn reward_minter_and_miners(
     env: &Env,
     glyph_owner: Address,
@@ -435,8 +551,8 @@ fn reward_minter_and_miners(
 ) -> Result<(), Error> {
     let mut leftover_amount = *amount;
 
-    // Get glyph
-    let glyph = read_glyph_or_error(env, hash);
+    // Get glyph, animations hold no colors of their own so they only pay out to the minter
+    let glyph = read_glyph_or_default(env, hash);
     let glyph_minter_address = read_glyph_minter(env, hash).ok_or(Error::NotFound)?;
 
     // Pay the glyph minter their cut
@@ -444,15 +560,70 @@ fn reward_minter_and_miners(
     let minter_amount = minter_royalty_rate.fixed_mul_ceil(*amount, 100).unwrap();
 
     let token = token::Client::new(env, asset);
+
+    // Contributors to a collaborative build split the minter's cut by pixels placed
+    let contributors = read_glyph_contributors(env, hash);
+    let mut minter_leftover_amount = minter_amount;
+
+    if !contributors.is_empty() {
+        let mut pixel_count: u32 = 0;
+
+        for (_, colors_indexes) in glyph.colors.iter() {
+            for (_, indexes) in colors_indexes.iter() {
+                pixel_count += indexes.len();
+            }
+        }
+
+        for (contributor_address, contributed) in contributors.iter() {
+            let contributor_amount = minter_amount
+                .fixed_mul_floor(contributed as i128, pixel_count as i128)
+                .unwrap()
+                .min(minter_leftover_amount);
+
+            if contributor_amount > 0 {
+                make_transfer(
+                    env,
+                    Some(&mut leftover_amount),
+                    &token,
+                    &asset_owner,
+                    &contributor_address,
+                    &contributor_amount,
+                );
+
+                minter_leftover_amount -= contributor_amount;
+            }
+        }
+    }
+
     make_transfer(
         env,
         Some(&mut leftover_amount),
         &token,
         &asset_owner,
         &glyph_minter_address,
-        &minter_amount,
+        &minter_leftover_amount,
     );
 
+    // Derivatives pay a cut to the minter of the glyph they were derived from
+    if let Some(parent_hash) = read_glyph_parent(env, hash) {
+        if let Some(parent_minter_address) = read_glyph_minter(env, &parent_hash) {
+            let parent_amount = read_derivative_royalty_rate(env)
+                .fixed_mul_ceil(*amount, 100)
+                .unwrap();
+
+            if parent_amount > 0 {
+                make_transfer(
+                    env,
+                    Some(&mut leftover_amount),
+                    &token,
+                    &asset_owner,
+                    &parent_minter_address,
+                    &parent_amount,
+                );
+            }
+        }
+    }
+
     // Loop over miners
     // NOTE currently can support 17 miners
     for (miner_address, colors_indexes) in glyph.colors.iter() {
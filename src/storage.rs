@@ -1,15 +1,176 @@
 use crate::types::{Error, StorageKey};
 use soroban_sdk::{panic_with_error, Address, Env};
 
+// Number of items stored in a single page of a paged index
+pub const PAGE_SIZE: u32 = 50;
+
+// Number of canvas pixels stored in a single entry, a 40x40 canvas spans 13 of them
+pub const CANVAS_CHUNK_SIZE: u32 = 128;
+
 pub mod persistent {
-    use soroban_sdk::{vec, BytesN, Map, Vec};
+    use soroban_sdk::{vec, BytesN, IntoVal, Map, TryFromVal, Val, Vec};
 
-    use crate::types::{Glyph, Offer};
+    use crate::types::{
+        Canvas, CanvasPixel, ColorAllowance, ColorCounters, ColorSupply, Glyph, GlyphApproval,
+        GlyphReservation, Layer, Offer, Stats,
+    };
 
     use super::*;
 
+    // Paged index utils
+    // Items are appended to the last page and removed by swapping in the very last item, keeping pages dense
+
+    fn paged_push<T>(
+        env: &Env,
+        count_key: &StorageKey,
+        page_key: impl Fn(u32) -> StorageKey,
+        item: T,
+    ) -> u32
+    where
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    {
+        let count = env
+            .storage()
+            .persistent()
+            .get::<StorageKey, u32>(count_key)
+            .unwrap_or(0);
+        let key = page_key(count / PAGE_SIZE);
+        let mut page = env
+            .storage()
+            .persistent()
+            .get::<StorageKey, Vec<T>>(&key)
+            .unwrap_or(Vec::new(env));
+
+        page.push_back(item);
+
+        env.storage().persistent().set(&key, &page);
+        env.storage().persistent().set(count_key, &(count + 1));
+
+        count
+    }
+
+    // Returns the item which was moved into `position` (if any) so the caller can update its position
+    fn paged_swap_remove<T>(
+        env: &Env,
+        count_key: &StorageKey,
+        page_key: impl Fn(u32) -> StorageKey,
+        position: u32,
+    ) -> Option<T>
+    where
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val> + Clone,
+    {
+        let count = env
+            .storage()
+            .persistent()
+            .get::<StorageKey, u32>(count_key)
+            .unwrap_or(0);
+
+        if position >= count {
+            return None;
+        }
+
+        let last = count - 1;
+        let last_key = page_key(last / PAGE_SIZE);
+        let mut last_page = env
+            .storage()
+            .persistent()
+            .get::<StorageKey, Vec<T>>(&last_key)
+            .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+        let last_item = last_page.pop_back().unwrap();
+        let mut moved = None;
+
+        if position != last {
+            if position / PAGE_SIZE == last / PAGE_SIZE {
+                last_page.set(position % PAGE_SIZE, last_item.clone());
+            } else {
+                let key = page_key(position / PAGE_SIZE);
+                let mut page = env
+                    .storage()
+                    .persistent()
+                    .get::<StorageKey, Vec<T>>(&key)
+                    .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+                page.set(position % PAGE_SIZE, last_item.clone());
+                env.storage().persistent().set(&key, &page);
+            }
+
+            moved = Some(last_item);
+        }
+
+        if last_page.is_empty() {
+            env.storage().persistent().remove(&last_key);
+        } else {
+            env.storage().persistent().set(&last_key, &last_page);
+        }
+
+        if last == 0 {
+            env.storage().persistent().remove(count_key);
+        } else {
+            env.storage().persistent().set(count_key, &last);
+        }
+
+        moved
+    }
+
+    fn paged_read<T>(
+        env: &Env,
+        count_key: &StorageKey,
+        page_key: impl Fn(u32) -> StorageKey,
+        cursor: u32,
+        limit: u32,
+    ) -> Vec<T>
+    where
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    {
+        let count = env
+            .storage()
+            .persistent()
+            .get::<StorageKey, u32>(count_key)
+            .unwrap_or(0);
+        let end = count.min(cursor.saturating_add(limit));
+        let mut items = Vec::new(env);
+        let mut position = cursor;
+
+        while position < end {
+            let page = env
+                .storage()
+                .persistent()
+                .get::<StorageKey, Vec<T>>(&page_key(position / PAGE_SIZE))
+                .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+            let mut i = position % PAGE_SIZE;
+
+            while i < page.len() && position < end {
+                items.push_back(page.get_unchecked(i));
+                i += 1;
+                position += 1;
+            }
+        }
+
+        items
+    }
+
     pub fn write_color(env: &Env, miner: &Address, to: &Address, color: u32, amount: u32) {
         let miner_owner_color = StorageKey::Color(miner.clone(), to.clone(), color);
+        let current_amount = read_color(env, miner, to, color);
+
+        // List the pair in the owner color registry the first time it's held
+        // Pairs stay listed once spent so spends don't pay for registry writes, `colors_of` skips them instead
+        if current_amount == 0 && amount > 0 {
+            add_owner_color(env, to, miner, color);
+        }
+
+        // Keep the owner's cross-miner color total in sync
+        // Balances written before totals were tracked are missing from them, so a total short of
+        // the balance being replaced is first brought up to it
+        if current_amount != amount {
+            let total =
+                read_color_total(env, to, color).max(current_amount) - current_amount + amount;
+
+            env.storage()
+                .persistent()
+                .set(&StorageKey::ColorTotal(to.clone(), color), &total);
+        }
 
         env.storage()
             .persistent()
@@ -25,6 +186,168 @@ pub mod persistent {
             .unwrap_or(0)
     }
 
+    pub fn read_color_total(env: &Env, owner: &Address, color: u32) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::ColorTotal(owner.clone(), color))
+            .unwrap_or(0)
+    }
+
+    // Supply is kept as running counters so balances that predate them can't throw it off
+    // Whatever has been mined and is neither burned nor locked up is sitting in wallets
+    pub fn read_color_supply(env: &Env, color: u32) -> ColorSupply {
+        let counters = read_color_counters(env, color);
+
+        ColorSupply {
+            mined: counters.mined,
+            burned: counters.burned,
+            locked: counters.locked,
+            wallets: counters
+                .mined
+                .saturating_sub(counters.burned)
+                .saturating_sub(counters.locked),
+        }
+    }
+
+    fn read_color_counters(env: &Env, color: u32) -> ColorCounters {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, ColorCounters>(&StorageKey::ColorSupply(color))
+            .unwrap_or_default()
+    }
+
+    fn write_color_counters(env: &Env, color: u32, counters: &ColorCounters) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::ColorSupply(color), counters);
+    }
+
+    pub fn write_color_mined(env: &Env, color: u32, amount: u32) {
+        let mut counters = read_color_counters(env, color);
+
+        counters.mined += amount as u64;
+
+        write_color_counters(env, color, &counters);
+    }
+
+    // Colors moving out of wallets and into glyphs or onto canvases
+    pub fn write_color_locked(env: &Env, color: u32, amount: u32) {
+        let mut counters = read_color_counters(env, color);
+
+        counters.locked += amount as u64;
+
+        write_color_counters(env, color, &counters);
+    }
+
+    // Colors leaving glyphs or canvases, either back into a wallet or burned
+    // Colors locked before the counters existed can come back out, hence the saturation
+    pub fn write_color_unlocked(env: &Env, color: u32, amount: u32, burned: bool) {
+        let mut counters = read_color_counters(env, color);
+
+        counters.locked = counters.locked.saturating_sub(amount as u64);
+
+        if burned {
+            counters.burned += amount as u64;
+        }
+
+        write_color_counters(env, color, &counters);
+    }
+
+    fn add_owner_color(env: &Env, owner: &Address, miner: &Address, color: u32) {
+        let index_key = StorageKey::OwnerColorIndex(owner.clone(), miner.clone(), color);
+
+        if env.storage().persistent().has(&index_key) {
+            return;
+        }
+
+        let position = paged_push(
+            env,
+            &StorageKey::OwnerColorCount(owner.clone()),
+            |page| StorageKey::OwnerColors(owner.clone(), page),
+            (miner.clone(), color),
+        );
+
+        env.storage().persistent().set(&index_key, &position);
+    }
+
+    pub fn read_owner_colors(
+        env: &Env,
+        owner: &Address,
+        cursor: u32,
+        limit: u32,
+    ) -> Vec<(Address, u32)> {
+        paged_read(
+            env,
+            &StorageKey::OwnerColorCount(owner.clone()),
+            |page| StorageKey::OwnerColors(owner.clone(), page),
+            cursor,
+            limit,
+        )
+    }
+
+    // Allowance-related storage utils
+
+    // Expired allowances read as empty
+    pub fn read_color_allowance(
+        env: &Env,
+        owner: &Address,
+        spender: &Address,
+        miner: &Address,
+        color: u32,
+    ) -> ColorAllowance {
+        let key = StorageKey::ColorAllowance(owner.clone(), spender.clone(), miner.clone(), color);
+
+        env.storage()
+            .persistent()
+            .get::<StorageKey, ColorAllowance>(&key)
+            .filter(|allowance| allowance.live_until_ledger >= env.ledger().sequence())
+            .unwrap_or(ColorAllowance {
+                amount: 0,
+                live_until_ledger: 0,
+            })
+    }
+
+    pub fn write_color_allowance(
+        env: &Env,
+        owner: &Address,
+        spender: &Address,
+        miner: &Address,
+        color: u32,
+        allowance: &ColorAllowance,
+    ) {
+        let key = StorageKey::ColorAllowance(owner.clone(), spender.clone(), miner.clone(), color);
+
+        if allowance.amount == 0 || allowance.live_until_ledger < env.ledger().sequence() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, allowance);
+        }
+    }
+
+    pub fn read_color_operator(env: &Env, owner: &Address, operator: &Address) -> bool {
+        let key = StorageKey::ColorOperator(owner.clone(), operator.clone());
+
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&key)
+            .is_some_and(|live_until_ledger| live_until_ledger >= env.ledger().sequence())
+    }
+
+    pub fn write_color_operator(
+        env: &Env,
+        owner: &Address,
+        operator: &Address,
+        live_until_ledger: u32,
+    ) {
+        let key = StorageKey::ColorOperator(owner.clone(), operator.clone());
+
+        if live_until_ledger < env.ledger().sequence() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &live_until_ledger);
+        }
+    }
+
     pub fn read_glyph_or_default(env: &Env, hash: &BytesN<32>) -> Glyph {
         read_glyph(env, hash).unwrap_or(Glyph {
             width: 0,
@@ -62,7 +385,143 @@ pub mod persistent {
     pub fn write_glyph_owner(env: &Env, hash: &BytesN<32>, new_owner: &Address) {
         let key = StorageKey::GlyphOwner(hash.clone());
 
-        env.storage().persistent().set(&key, &new_owner);
+        // Keep the owner index in sync for glyphs which are already indexed (i.e. fully minted)
+        if env
+            .storage()
+            .persistent()
+            .has(&StorageKey::OwnerGlyphIndex(hash.clone()))
+        {
+            if let Some(owner) = env.storage().persistent().get::<StorageKey, Address>(&key) {
+                if owner != *new_owner {
+                    remove_owner_glyph(env, &owner, hash);
+                    add_owner_glyph(env, new_owner, hash);
+                }
+            }
+        }
+
+        env.storage().persistent().set(&key, new_owner);
+
+        // Approvals don't survive a change of hands
+        remove_glyph_approval(env, hash);
+    }
+
+    // Expired approvals read as empty
+    pub fn read_glyph_approval(env: &Env, hash: &BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, GlyphApproval>(&StorageKey::GlyphApproval(hash.clone()))
+            .filter(|approval| approval.live_until_ledger >= env.ledger().sequence())
+            .map(|approval| approval.approved)
+    }
+
+    pub fn write_glyph_approval(env: &Env, hash: &BytesN<32>, approval: &GlyphApproval) {
+        let key = StorageKey::GlyphApproval(hash.clone());
+
+        if approval.live_until_ledger < env.ledger().sequence() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, approval);
+        }
+    }
+
+    pub fn remove_glyph_approval(env: &Env, hash: &BytesN<32>) {
+        let key = StorageKey::GlyphApproval(hash.clone());
+
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    pub fn read_glyph_operator(env: &Env, owner: &Address, operator: &Address) -> bool {
+        let key = StorageKey::GlyphOperator(owner.clone(), operator.clone());
+
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&key)
+            .is_some_and(|live_until_ledger| live_until_ledger >= env.ledger().sequence())
+    }
+
+    pub fn write_glyph_operator(
+        env: &Env,
+        owner: &Address,
+        operator: &Address,
+        live_until_ledger: u32,
+    ) {
+        let key = StorageKey::GlyphOperator(owner.clone(), operator.clone());
+
+        if live_until_ledger < env.ledger().sequence() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &live_until_ledger);
+        }
+    }
+
+    // Owner index storage utils
+
+    pub fn add_owner_glyph(env: &Env, owner: &Address, hash: &BytesN<32>) {
+        let index_key = StorageKey::OwnerGlyphIndex(hash.clone());
+
+        if env.storage().persistent().has(&index_key) {
+            return;
+        }
+
+        let position = paged_push(
+            env,
+            &StorageKey::OwnerGlyphCount(owner.clone()),
+            |page| StorageKey::OwnerGlyphs(owner.clone(), page),
+            hash.clone(),
+        );
+
+        env.storage().persistent().set(&index_key, &position);
+    }
+
+    pub fn remove_owner_glyph(env: &Env, owner: &Address, hash: &BytesN<32>) {
+        let index_key = StorageKey::OwnerGlyphIndex(hash.clone());
+        let position = match env
+            .storage()
+            .persistent()
+            .get::<StorageKey, u32>(&index_key)
+        {
+            Some(position) => position,
+            None => return,
+        };
+
+        let moved = paged_swap_remove::<BytesN<32>>(
+            env,
+            &StorageKey::OwnerGlyphCount(owner.clone()),
+            |page| StorageKey::OwnerGlyphs(owner.clone(), page),
+            position,
+        );
+
+        if let Some(moved_hash) = moved {
+            env.storage()
+                .persistent()
+                .set(&StorageKey::OwnerGlyphIndex(moved_hash), &position);
+        }
+
+        env.storage().persistent().remove(&index_key);
+    }
+
+    pub fn read_owner_glyphs(
+        env: &Env,
+        owner: &Address,
+        cursor: u32,
+        limit: u32,
+    ) -> Vec<BytesN<32>> {
+        paged_read(
+            env,
+            &StorageKey::OwnerGlyphCount(owner.clone()),
+            |page| StorageKey::OwnerGlyphs(owner.clone(), page),
+            cursor,
+            limit,
+        )
+    }
+
+    pub fn read_owner_glyph_count(env: &Env, owner: &Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::OwnerGlyphCount(owner.clone()))
+            .unwrap_or(0)
     }
 
     pub fn remove_glyph_offer(env: &Env, hash: &BytesN<32>) {
@@ -74,6 +533,262 @@ pub mod persistent {
         }
     }
 
+    // Pixels placed in a glyph by allowlisted contributors other than its minter
+    pub fn read_glyph_contributors(env: &Env, hash: &BytesN<32>) -> Map<Address, u32> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::GlyphContributors(hash.clone()))
+            .unwrap_or(Map::new(env))
+    }
+
+    pub fn write_glyph_contributors(
+        env: &Env,
+        hash: &BytesN<32>,
+        contributors: &Map<Address, u32>,
+    ) {
+        let key = StorageKey::GlyphContributors(hash.clone());
+
+        if contributors.is_empty() {
+            if env.storage().persistent().has(&key) {
+                env.storage().persistent().remove(&key);
+            }
+        } else {
+            env.storage().persistent().set(&key, contributors);
+        }
+    }
+
+    // Who paid for which colors of a pending build, so a cancel can hand them back to the right wallets
+    pub fn read_glyph_spends(env: &Env, hash: &BytesN<32>) -> Map<(Address, Address, u32), u32> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::GlyphSpends(hash.clone()))
+            .unwrap_or(Map::new(env))
+    }
+
+    pub fn write_glyph_spends(
+        env: &Env,
+        hash: &BytesN<32>,
+        spends: &Map<(Address, Address, u32), u32>,
+    ) {
+        let key = StorageKey::GlyphSpends(hash.clone());
+
+        if spends.is_empty() {
+            if env.storage().persistent().has(&key) {
+                env.storage().persistent().remove(&key);
+            }
+        } else {
+            env.storage().persistent().set(&key, spends);
+        }
+    }
+
+    // Global counters live outside instance storage so they don't weigh on every call
+    pub fn read_stats(env: &Env) -> Stats {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::Stats)
+            .unwrap_or_default()
+    }
+
+    pub fn write_stats(env: &Env, stats: &Stats) {
+        env.storage().persistent().set(&StorageKey::Stats, stats);
+    }
+
+    // Animation-related storage utils
+
+    pub fn read_animation(env: &Env, hash: &BytesN<32>) -> Option<Vec<(BytesN<32>, u32)>> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::Animation(hash.clone()))
+    }
+
+    pub fn write_animation(env: &Env, hash: &BytesN<32>, frames: &Vec<(BytesN<32>, u32)>) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::Animation(hash.clone()), frames);
+    }
+
+    pub fn remove_animation(env: &Env, hash: &BytesN<32>) {
+        let key = StorageKey::Animation(hash.clone());
+
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    // Composite-related storage utils
+
+    pub fn read_composite(env: &Env, hash: &BytesN<32>) -> Option<Vec<Layer>> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::Composite(hash.clone()))
+    }
+
+    pub fn write_composite(env: &Env, hash: &BytesN<32>, layers: &Vec<Layer>) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::Composite(hash.clone()), layers);
+    }
+
+    pub fn remove_composite(env: &Env, hash: &BytesN<32>) {
+        let key = StorageKey::Composite(hash.clone());
+
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    // Reservation-related storage utils
+
+    pub fn read_reservation_commitment(
+        env: &Env,
+        reserver: &Address,
+        commitment: &BytesN<32>,
+    ) -> Option<(u32, u32)> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, (u32, u32)>(&StorageKey::ReservationCommitment(
+                reserver.clone(),
+                commitment.clone(),
+            ))
+            .filter(|(_, live_until_ledger)| *live_until_ledger >= env.ledger().sequence())
+    }
+
+    pub fn write_reservation_commitment(
+        env: &Env,
+        reserver: &Address,
+        commitment: &BytesN<32>,
+        live_until_ledger: u32,
+    ) {
+        env.storage().persistent().set(
+            &StorageKey::ReservationCommitment(reserver.clone(), commitment.clone()),
+            &(env.ledger().sequence(), live_until_ledger),
+        );
+    }
+
+    pub fn remove_reservation_commitment(env: &Env, reserver: &Address, commitment: &BytesN<32>) {
+        let key = StorageKey::ReservationCommitment(reserver.clone(), commitment.clone());
+
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    pub fn read_glyph_reservation(env: &Env, hash: &BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, GlyphReservation>(&StorageKey::GlyphReservation(hash.clone()))
+            .filter(|reservation| reservation.live_until_ledger >= env.ledger().sequence())
+            .map(|reservation| reservation.reserver)
+    }
+
+    pub fn write_glyph_reservation(env: &Env, hash: &BytesN<32>, reservation: &GlyphReservation) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphReservation(hash.clone()), reservation);
+    }
+
+    pub fn remove_glyph_reservation(env: &Env, hash: &BytesN<32>) {
+        let key = StorageKey::GlyphReservation(hash.clone());
+
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    // Lineage-related storage utils
+
+    pub fn read_glyph_parent(env: &Env, hash: &BytesN<32>) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::GlyphParent(hash.clone()))
+    }
+
+    pub fn write_glyph_parent(env: &Env, hash: &BytesN<32>, parent: &BytesN<32>) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphParent(hash.clone()), parent);
+    }
+
+    pub fn remove_glyph_parent(env: &Env, hash: &BytesN<32>) {
+        let key = StorageKey::GlyphParent(hash.clone());
+
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    pub fn add_glyph_child(env: &Env, parent: &BytesN<32>, hash: &BytesN<32>) {
+        let position = paged_push(
+            env,
+            &StorageKey::GlyphChildCount(parent.clone()),
+            |page| StorageKey::GlyphChildren(parent.clone(), page),
+            hash.clone(),
+        );
+
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphChildIndex(hash.clone()), &position);
+    }
+
+    pub fn remove_glyph_child(env: &Env, parent: &BytesN<32>, hash: &BytesN<32>) {
+        let index_key = StorageKey::GlyphChildIndex(hash.clone());
+        let position = match env
+            .storage()
+            .persistent()
+            .get::<StorageKey, u32>(&index_key)
+        {
+            Some(position) => position,
+            None => return,
+        };
+
+        let moved = paged_swap_remove::<BytesN<32>>(
+            env,
+            &StorageKey::GlyphChildCount(parent.clone()),
+            |page| StorageKey::GlyphChildren(parent.clone(), page),
+            position,
+        );
+
+        if let Some(moved_hash) = moved {
+            env.storage()
+                .persistent()
+                .set(&StorageKey::GlyphChildIndex(moved_hash), &position);
+        }
+
+        env.storage().persistent().remove(&index_key);
+    }
+
+    pub fn read_glyph_children(
+        env: &Env,
+        parent: &BytesN<32>,
+        cursor: u32,
+        limit: u32,
+    ) -> Vec<BytesN<32>> {
+        paged_read(
+            env,
+            &StorageKey::GlyphChildCount(parent.clone()),
+            |page| StorageKey::GlyphChildren(parent.clone(), page),
+            cursor,
+            limit,
+        )
+    }
+
+    pub fn read_glyph_derive_locked(env: &Env, parent: &BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::GlyphDeriveLocked(parent.clone()))
+            .unwrap_or(false)
+    }
+
+    pub fn write_glyph_derive_locked(env: &Env, parent: &BytesN<32>, locked: bool) {
+        let key = StorageKey::GlyphDeriveLocked(parent.clone());
+
+        if locked {
+            env.storage().persistent().set(&key, &true);
+        } else if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
     pub fn read_glyph_minter(env: &Env, hash: &BytesN<32>) -> Option<Address> {
         let buy_glyph_minter_key = StorageKey::GlyphMinter(hash.clone());
 
@@ -82,6 +797,12 @@ pub mod persistent {
             .get::<StorageKey, Address>(&buy_glyph_minter_key)
     }
 
+    pub fn write_glyph_minter(env: &Env, hash: &BytesN<32>, minter: &Address) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphMinter(hash.clone()), minter);
+    }
+
     // Offers-related storage utils
 
     pub fn read_offers_by_glyph(env: &Env, hash: &BytesN<32>) -> Vec<Offer> {
@@ -123,6 +844,13 @@ pub mod persistent {
         if env.storage().persistent().has(&key) {
             // TODO should we emit an offer remove event here?
             env.storage().persistent().remove(&key);
+
+            let mut keys = read_asset_offer_keys(env, hash);
+
+            if let Some(index) = keys.first_index_of((address.clone(), amount)) {
+                keys.remove(index);
+                write_asset_offer_keys(env, hash, &keys);
+            }
         }
     }
 
@@ -146,11 +874,93 @@ pub mod persistent {
     ) {
         let key = StorageKey::AssetOffer(hash.clone(), address.clone(), amount);
 
+        // Checking the keys rather than the entry also picks up bids posted before keys were tracked
+        index_asset_offers_by_asset(env, hash, address, amount);
+
         env.storage().persistent().set(&key, offers);
     }
+
+    pub fn index_asset_offers_by_asset(
+        env: &Env,
+        hash: &BytesN<32>,
+        address: &Address,
+        amount: i128,
+    ) {
+        let mut keys = read_asset_offer_keys(env, hash);
+
+        if !keys.contains((address.clone(), amount)) {
+            keys.push_back((address.clone(), amount));
+            write_asset_offer_keys(env, hash, &keys);
+        }
+    }
+
+    // Every (sac, amount) pair with open bids on a glyph, so they can be found again without knowing the amounts
+    pub fn read_asset_offer_keys(env: &Env, hash: &BytesN<32>) -> Vec<(Address, i128)> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::AssetOfferKeys(hash.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn write_asset_offer_keys(env: &Env, hash: &BytesN<32>, keys: &Vec<(Address, i128)>) {
+        let key = StorageKey::AssetOfferKeys(hash.clone());
+
+        if keys.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, keys);
+        }
+    }
+
+    // Canvas-related storage utils
+
+    pub fn read_canvas(env: &Env, id: u32) -> Option<Canvas> {
+        env.storage().persistent().get(&StorageKey::Canvas(id))
+    }
+
+    pub fn write_canvas(env: &Env, id: u32, canvas: &Canvas) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::Canvas(id), canvas);
+    }
+
+    pub fn read_canvas_chunk(env: &Env, id: u32, chunk: u32) -> Map<u32, CanvasPixel> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::CanvasChunk(id, chunk))
+            .unwrap_or(Map::new(env))
+    }
+
+    pub fn write_canvas_chunk(env: &Env, id: u32, chunk: u32, pixels: &Map<u32, CanvasPixel>) {
+        let key = StorageKey::CanvasChunk(id, chunk);
+
+        if pixels.is_empty() {
+            if env.storage().persistent().has(&key) {
+                env.storage().persistent().remove(&key);
+            }
+        } else {
+            env.storage().persistent().set(&key, pixels);
+        }
+    }
+
+    pub fn read_canvas_snapshot(env: &Env, hash: &BytesN<32>) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&StorageKey::CanvasSnapshot(hash.clone()))
+    }
+
+    pub fn write_canvas_snapshot(env: &Env, hash: &BytesN<32>, id: u32) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::CanvasSnapshot(hash.clone()), &id);
+    }
 }
 
 pub mod instance {
+    use soroban_sdk::String;
+
+    use crate::types::MinePricing;
+
     use super::*;
 
     pub fn write_owner_address(env: &Env, owner: &Address) {
@@ -201,6 +1011,26 @@ pub mod instance {
             .set(&StorageKey::MinerRoyaltyRate, miner_royalty_rate);
     }
 
+    pub fn write_derivative_royalty_rate(env: &Env, derivative_royalty_rate: &i128) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::DerivativeRoyaltyRate, derivative_royalty_rate);
+    }
+
+    pub fn write_mine_pricing(env: &Env, mine_pricing: &Option<MinePricing>) {
+        match mine_pricing {
+            Some(mine_pricing) => env
+                .storage()
+                .instance()
+                .set(&StorageKey::MinePricing, mine_pricing),
+            None => env.storage().instance().remove(&StorageKey::MinePricing),
+        }
+    }
+
+    pub fn write_base_uri(env: &Env, base_uri: &String) {
+        env.storage().instance().set(&StorageKey::BaseUri, base_uri);
+    }
+
     pub fn read_owner_address(env: &Env) -> Address {
         env.storage()
             .instance()
@@ -250,10 +1080,43 @@ pub mod instance {
             .unwrap_or_else(|| panic_with_error!(env, Error::NotInitialized))
     }
 
+    // Deployments from before derivatives existed simply don't pay parents
+    pub fn read_derivative_royalty_rate(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::DerivativeRoyaltyRate)
+            .unwrap_or(0)
+    }
+
     pub fn read_miner_royalty_rate(env: &Env) -> i128 {
         env.storage()
             .instance()
             .get(&StorageKey::MinerRoyaltyRate)
             .unwrap_or_else(|| panic_with_error!(env, Error::NotInitialized))
     }
+
+    pub fn write_canvas_count(env: &Env, count: &u32) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::CanvasCount, count);
+    }
+
+    pub fn read_canvas_count(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::CanvasCount)
+            .unwrap_or(0)
+    }
+
+    // No pricing set means every pixel costs a flat `MineMultiplier`
+    pub fn read_mine_pricing(env: &Env) -> Option<MinePricing> {
+        env.storage().instance().get(&StorageKey::MinePricing)
+    }
+
+    pub fn read_base_uri(env: &Env) -> String {
+        env.storage()
+            .instance()
+            .get(&StorageKey::BaseUri)
+            .unwrap_or(String::from_str(env, ""))
+    }
 }
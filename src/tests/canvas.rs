@@ -0,0 +1,216 @@
+#![cfg(test)]
+
+use crate::{
+    contract::{ColorGlyph, ColorGlyphClient},
+    glyphs::glyph_hash,
+    types::{CanvasPixel, ColorSupply, Error, GlyphStatus},
+};
+use soroban_sdk::{map, testutils::Address as _, token, vec, Address, Env, Map};
+
+#[test]
+fn test_canvas() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 2)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (1, 2)], &None, &None, &None);
+
+    let id = client.canvas_create(&u1_address, &2, &2, &false);
+
+    client.canvas_paint(
+        &u1_address,
+        &id,
+        &map![
+            &env,
+            (u1_address.clone(), map![&env, (0, vec![&env, 0, 1])])
+        ],
+    );
+    client.canvas_paint(
+        &u2_address,
+        &id,
+        &map![
+            &env,
+            (u2_address.clone(), map![&env, (1, vec![&env, 1, 3])])
+        ],
+    );
+
+    // Painted over pixels go back to whoever painted them
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 1);
+    assert_eq!(client.color_balance(&u2_address, &1, &None), 0);
+    assert_eq!(
+        client.color_supply(&0),
+        ColorSupply {
+            mined: 2,
+            burned: 0,
+            locked: 1,
+            wallets: 1
+        }
+    );
+    assert_eq!(
+        client.canvas_pixels(&id, &0, &4),
+        vec![
+            &env,
+            (
+                0,
+                CanvasPixel {
+                    painter: u1_address.clone(),
+                    miner: u1_address.clone(),
+                    color: 0
+                }
+            ),
+            (
+                1,
+                CanvasPixel {
+                    painter: u2_address.clone(),
+                    miner: u2_address.clone(),
+                    color: 1
+                }
+            ),
+            (
+                3,
+                CanvasPixel {
+                    painter: u2_address.clone(),
+                    miner: u2_address.clone(),
+                    color: 1
+                }
+            ),
+        ]
+    );
+
+    let (hash, _) = glyph_hash(
+        &env,
+        &map![
+            &env,
+            (u1_address.clone(), map![&env, (0, vec![&env, 0])]),
+            (u2_address.clone(), map![&env, (1, vec![&env, 1, 3])])
+        ],
+        2,
+    );
+
+    assert_eq!(client.canvas_freeze(&id, &None), hash);
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Minted);
+    assert_eq!(client.owner_of(&hash), u1_address);
+    assert_eq!(
+        client.glyph_contributors(&hash),
+        map![&env, (u2_address.clone(), 2)]
+    );
+
+    // The canvas carries on and its colors stay with it rather than the snapshot
+    assert_eq!(client.canvas_get(&id).owner, u1_address);
+    assert_eq!(client.canvas_pixels(&id, &0, &4).len(), 3);
+    assert_eq!(
+        client.try_glyph_scrape(&None, &hash),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        client.try_glyph_repaint(
+            &hash,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])],
+            &Map::new(&env),
+            &2
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        client.try_canvas_freeze(&id, &None),
+        Err(Ok(Error::NotEmpty))
+    );
+
+    client.canvas_paint(
+        &u1_address,
+        &id,
+        &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 2])])],
+    );
+
+    let (hash, _) = glyph_hash(
+        &env,
+        &map![
+            &env,
+            (u1_address.clone(), map![&env, (0, vec![&env, 0, 2])]),
+            (u2_address.clone(), map![&env, (1, vec![&env, 1, 3])])
+        ],
+        2,
+    );
+
+    assert_eq!(client.canvas_freeze(&id, &Some(u2_address.clone())), hash);
+    assert_eq!(client.owner_of(&hash), u2_address);
+
+    // Pixels are read across the chunks they're stored in
+    client.colors_mine(&u2_address, &map![&env, (1, 3)], &None, &None, &None);
+
+    let id = client.canvas_create(&u2_address, &20, &20, &false);
+
+    client.canvas_paint(
+        &u2_address,
+        &id,
+        &map![
+            &env,
+            (u2_address.clone(), map![&env, (1, vec![&env, 5, 150])])
+        ],
+    );
+
+    assert_eq!(
+        client.canvas_pixels(&id, &100, &100),
+        vec![
+            &env,
+            (
+                150,
+                CanvasPixel {
+                    painter: u2_address.clone(),
+                    miner: u2_address.clone(),
+                    color: 1
+                }
+            )
+        ]
+    );
+    assert_eq!(client.canvas_pixels(&id, &0, &400).len(), 2);
+
+    // Painted over pixels on a burning canvas are gone for good
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    let id = client.canvas_create(&u1_address, &1, &1, &true);
+
+    client.canvas_paint(
+        &u1_address,
+        &id,
+        &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])],
+    );
+    client.canvas_paint(
+        &u2_address,
+        &id,
+        &map![&env, (u2_address.clone(), map![&env, (1, vec![&env, 0])])],
+    );
+
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 0);
+    assert_eq!(
+        client.color_supply(&0),
+        ColorSupply {
+            mined: 3,
+            burned: 1,
+            locked: 2,
+            wallets: 0
+        }
+    );
+}
@@ -3,8 +3,12 @@
 // use std::println;
 // extern crate std;
 
-use crate::contract::{ColorGlyph, ColorGlyphClient};
-use soroban_sdk::{testutils::Address as _, token, vec, Address, Env, Map};
+use crate::{
+    contract::{ColorGlyph, ColorGlyphClient},
+    glyphs::glyph_hash,
+    types::{ColorAllowance, Error, MinePricing, PriceCurve, StorageKey},
+};
+use soroban_sdk::{map, testutils::Address as _, token, vec, Address, Env, Map};
 
 #[test]
 fn test() {
@@ -42,13 +46,19 @@ fn test() {
         colors.set(i, 1);
     }
 
-    client.colors_mine(&u1_address, &colors, &None, &None);
+    client.colors_mine(&u1_address, &colors, &None, &None, &None);
 
     let color = client.color_balance(&u1_address.clone(), &0, &None);
 
     assert_eq!(color, 1);
 
-    client.colors_mine(&u2_address, &colors, &None, &Some(u1_address.clone()));
+    client.colors_mine(
+        &u2_address,
+        &colors,
+        &None,
+        &Some(u1_address.clone()),
+        &None,
+    );
 
     let color1 = client.color_balance(&u1_address.clone(), &0, &None);
     let color2 = client.color_balance(&u1_address.clone(), &0, &Option::Some(u2_address.clone()));
@@ -72,9 +82,315 @@ fn test() {
     assert_eq!(color0, 0); // ensure we test for colors that don't exist (getting and bumping non-existent values)
     assert_eq!(color1 + color2, 2);
 
+    assert_eq!(
+        client.color_balances(
+            &u3_address,
+            &vec![
+                &env,
+                (Some(u1_address.clone()), 0),
+                (Some(u2_address.clone()), 0),
+                (None, 0)
+            ]
+        ),
+        vec![&env, 1, 1, 0]
+    );
+    assert_eq!(client.color_balance_total(&u3_address, &0), 2);
+    assert_eq!(client.color_balance_total(&u1_address, &0), 0);
+
     assert_eq!(token_client.balance(&u1_address), 10_000 - 256);
     assert_eq!(token_client.balance(&u2_address), 10_000 - 256);
     assert_eq!(token_client.balance(&fee_address), 512);
 
     // println!("{:?}", env.budget().print());
 }
+
+#[test]
+fn test_colors_of() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(
+        &u1_address,
+        &map![&env, (0, 2), (1, 3)],
+        &None,
+        &None,
+        &None,
+    );
+    client.colors_mine(
+        &u2_address,
+        &map![&env, (0, 4)],
+        &None,
+        &Some(u1_address.clone()),
+        &None,
+    );
+
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![
+            &env,
+            (u1_address.clone(), 0, 2),
+            (u1_address.clone(), 1, 3),
+            (u2_address.clone(), 0, 4)
+        ]
+    );
+
+    client.colors_transfer(
+        &u1_address,
+        &u2_address,
+        &vec![&env, (u1_address.clone(), 0, 2)],
+    );
+
+    // Spent out pairs keep their place but aren't returned
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![&env, (u1_address.clone(), 1, 3), (u2_address.clone(), 0, 4)]
+    );
+    assert_eq!(client.colors_of(&u1_address, &0, &1).len(), 0);
+    assert_eq!(client.colors_of(&u1_address, &1, &10).len(), 2);
+    assert_eq!(
+        client.colors_of(&u2_address, &0, &10),
+        vec![&env, (u1_address.clone(), 0, 2)]
+    );
+
+    // Getting a spent out pair back reuses its place
+    client.colors_transfer(
+        &u2_address,
+        &u1_address,
+        &vec![&env, (u1_address.clone(), 0, 1)],
+    );
+
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![
+            &env,
+            (u1_address.clone(), 0, 1),
+            (u1_address.clone(), 1, 3),
+            (u2_address.clone(), 0, 4)
+        ]
+    );
+}
+
+#[test]
+fn test_legacy_balance() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // A balance written before color totals were tracked
+    env.as_contract(&contract_address, || {
+        env.storage().persistent().set(
+            &StorageKey::Color(u1_address.clone(), u1_address.clone(), 0),
+            &5u32,
+        );
+    });
+
+    assert_eq!(client.color_balance_total(&u1_address, &0), 0);
+
+    client.colors_transfer(
+        &u1_address,
+        &u2_address,
+        &vec![&env, (u1_address.clone(), 0, 2)],
+    );
+
+    assert_eq!(
+        client.color_balance(&u1_address, &0, &Some(u1_address.clone())),
+        3
+    );
+    assert_eq!(client.color_balance_total(&u1_address, &0), 3);
+    assert_eq!(client.color_balance_total(&u2_address, &0), 2);
+}
+
+#[test]
+fn test_allowances() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(
+        &u1_address,
+        &map![&env, (0, 2), (1, 2)],
+        &None,
+        &None,
+        &None,
+    );
+
+    client.colors_approve(&u1_address, &u2_address, &u1_address, &0, &2, &100);
+
+    client.colors_transfer_from(
+        &u2_address,
+        &u1_address,
+        &u3_address,
+        &vec![&env, (u1_address.clone(), 0, 1)],
+    );
+
+    assert_eq!(
+        client.colors_allowance(&u1_address, &u2_address, &u1_address, &0),
+        ColorAllowance {
+            amount: 1,
+            live_until_ledger: 100
+        }
+    );
+    assert_eq!(
+        client.color_balance(&u3_address, &0, &Some(u1_address.clone())),
+        1
+    );
+
+    // A studio mints with a member's colors
+    client.colors_set_operator(&u1_address, &u2_address, &100);
+
+    assert!(client.colors_is_operator(&u1_address, &u2_address));
+
+    let colors = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![&env, (0, vec![&env, 0]), (1, vec![&env, 1, 2])]
+        )
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 3);
+
+    client.glyph_mint_from(&hash, &u2_address, &u1_address, &None, &colors, &Some(3));
+
+    assert_eq!(client.color_balance_total(&u1_address, &0), 0);
+    assert_eq!(client.color_balance_total(&u1_address, &1), 0);
+    assert_eq!(client.glyphs_of(&u2_address, &0, &10), vec![&env, hash]);
+}
+
+#[test]
+fn test_mine_pricing() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &2);
+
+    // Tests
+    client.mine_pricing_set(&Some(MinePricing {
+        curve: PriceCurve::Linear,
+        rate: 1,
+        floor: 0,
+        cap: 5,
+    }));
+
+    // 2 + 3 + 4 + 5 + 5
+    assert_eq!(client.colors_mine_quote(&map![&env, (0, 5)]), 19);
+    assert_eq!(
+        client.try_colors_mine(&u1_address, &map![&env, (0, 5)], &None, &None, &Some(18)),
+        Err(Ok(Error::MaxCostExceeded))
+    );
+    assert_eq!(
+        client.colors_mine(&u1_address, &map![&env, (0, 5)], &None, &None, &Some(19)),
+        19
+    );
+    assert_eq!(token_client.balance(&fee_address), 19);
+
+    // Capped from here on
+    assert_eq!(client.colors_mine_quote(&map![&env, (0, 2)]), 10);
+
+    client.mine_pricing_set(&Some(MinePricing {
+        curve: PriceCurve::Exponential,
+        rate: 2,
+        floor: 3,
+        cap: 16,
+    }));
+
+    // 3 + 3 + 4 + 4 + 8 + 8 + 16
+    assert_eq!(client.colors_mine_quote(&map![&env, (1, 7)]), 46);
+
+    // With no base there's nothing to double so the floor holds however far out the pixels go
+    client.update(&None, &None, &None, &None, &None, &Some(0), &None, &None);
+
+    assert_eq!(client.colors_mine_quote(&map![&env, (1, 300)]), 900);
+
+    client.update(&None, &None, &None, &None, &None, &Some(2), &None, &None);
+
+    assert_eq!(
+        client.try_mine_pricing_set(&Some(MinePricing {
+            curve: PriceCurve::Exponential,
+            rate: 0,
+            floor: 0,
+            cap: 16,
+        })),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    client.mine_pricing_set(&None);
+
+    assert_eq!(client.colors_mine_quote(&map![&env, (1, 7)]), 14);
+}
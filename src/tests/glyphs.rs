@@ -4,14 +4,15 @@ use std::println;
 extern crate std;
 
 use crate::{
-    contract::{ColorGlyph, ColorGlyphClient},
-    types::{Error, StorageKey},
+    contract::{ColorGlyph, ColorGlyphClient, COLOR_PAYMENT_ENTRIES, MAX_RESERVATION_LEDGERS},
+    glyphs::glyph_hash,
+    types::{Bitmap, ColorSupply, Error, GlyphStatus, Layer, MintQuote, StorageKey, Transform},
 };
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{
     map,
-    testutils::{Address as _, BytesN as _},
-    token, vec, Address, BytesN, Env,
+    testutils::{Address as _, BytesN as _, Ledger},
+    token, vec, Address, Bytes, BytesN, Env, String,
 };
 
 mod colorglyph {
@@ -21,7 +22,6 @@ mod colorglyph {
 }
 
 /* TODO
-test scrape of a never minted glyph
 test partial scrape to mint
 test partial scrape to build then remint
 test full scrape to remint
@@ -74,7 +74,7 @@ fn big_mint() {
         }
     }
 
-    client.colors_mine(&u1_address, &mine_colors, &None, &None);
+    client.colors_mine(&u1_address, &mine_colors, &None, &None, &None);
 
     let hash = BytesN::from_array(
         &env,
@@ -152,6 +152,7 @@ fn toolbox_test() {
         &map![&env, (0, 100), (16777215, 100),],
         &None,
         &None,
+        &None,
     );
 
     let hash = BytesN::random(&env);
@@ -201,6 +202,7 @@ fn test_dupe_mint() {
         &map![&env, (0, 100), (16777215, 100),],
         &None,
         &None,
+        &None,
     );
 
     let hash = BytesN::from_array(
@@ -241,7 +243,7 @@ fn test_dupe_mint() {
             ],
             &Some(2),
         ),
-        Err(Ok(soroban_sdk::Error::from(Error::NotEmpty)))
+        Err(Ok(Error::NotEmpty))
     );
 }
 
@@ -283,6 +285,7 @@ fn test_to_mint() {
         ],
         &None,
         &None,
+        &None,
     );
 
     let hash = BytesN::from_array(
@@ -388,6 +391,7 @@ fn test_partial_mint() {
         ],
         &None,
         &None,
+        &None,
     );
     client.colors_mine(
         &u2_address,
@@ -406,6 +410,7 @@ fn test_partial_mint() {
         ],
         &None,
         &Some(u1_address.clone()),
+        &None,
     );
     client.colors_mine(
         &u1_address,
@@ -424,6 +429,7 @@ fn test_partial_mint() {
         ],
         &None,
         &None,
+        &None,
     );
 
     let hash = BytesN::from_array(
@@ -474,6 +480,10 @@ fn test_partial_mint() {
         ],
         &None,
     );
+
+    // Continuing your own build takes a single signature
+    assert_eq!(env.auths().len(), 1);
+
     client.glyph_mint(
         &hash,
         &u1_address,
@@ -623,11 +633,1223 @@ fn test_partial_mint() {
 
     client.glyph_scrape(&None, &hash.clone());
 
-    assert_eq!(client.glyph_get(&hash.clone()).colors.len(), 1);
+    // 30 colors at 3 color payments per call
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(27, 9));
 
-    client.glyph_scrape(&None, &hash.clone());
+    for _ in 0..9 {
+        client.glyph_scrape(&None, &hash.clone());
+    }
 
     assert_eq!(client.glyph_get(&hash.clone()).colors.len(), 0);
 
     // println!("{:?}", env.budget().print());
 }
+
+#[test]
+fn test_mint_cancel() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Two color payments per call
+    client.update(
+        &None,
+        &None,
+        &None,
+        &None,
+        &Some(2 * COLOR_PAYMENT_ENTRIES),
+        &None,
+        &None,
+        &None,
+    );
+
+    client.colors_mine(
+        &u1_address,
+        &map![&env, (0, 1), (1, 1), (2, 1)],
+        &None,
+        &None,
+        &None,
+    );
+
+    let colors = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![
+                &env,
+                (0, vec![&env, 0]),
+                (1, vec![&env, 1]),
+                (2, vec![&env, 2])
+            ]
+        )
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 3);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &None);
+
+    assert_eq!(client.color_balance_total(&u1_address, &0), 0);
+
+    client.glyph_mint_cancel(&hash);
+
+    assert_eq!(client.glyph_get(&hash).colors.get(u1_address.clone()).unwrap().len(), 1);
+    assert_eq!(client.color_balance_total(&u1_address, &0), 1);
+    assert_eq!(client.color_balance_total(&u1_address, &1), 1);
+
+    client.glyph_mint_cancel(&hash);
+
+    assert_eq!(client.color_balance_total(&u1_address, &2), 1);
+    assert_eq!(client.try_owner_of(&hash), Err(Ok(Error::NotFound)));
+
+    env.as_contract(&contract_address, || {
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&StorageKey::Glyph(hash.clone())));
+    });
+
+    // The hash is free to be minted again from scratch
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(3));
+
+    assert_eq!(
+        client.try_glyph_mint_cancel(&hash),
+        Err(Ok(Error::NotEmpty))
+    );
+}
+
+#[test]
+fn test_mint_cancel_refunds() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+    token_admin_client.mint(&u3_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (1, 2)], &None, &None, &None);
+    client.colors_mine(&u3_address, &map![&env, (2, 1)], &None, &None, &None);
+
+    let u1_colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let u2_colors = map![&env, (u2_address.clone(), map![&env, (1, vec![&env, 1, 2])])];
+    let u3_colors = map![&env, (u3_address.clone(), map![&env, (2, vec![&env, 3])])];
+    let (hash, _) = glyph_hash(
+        &env,
+        &map![
+            &env,
+            (u1_address.clone(), map![&env, (0, vec![&env, 0])]),
+            (u2_address.clone(), map![&env, (1, vec![&env, 1, 2])]),
+            (u3_address.clone(), map![&env, (2, vec![&env, 3])])
+        ],
+        4,
+    );
+
+    // The owner starts the build, a contributor adds to it and the owner spends an operator's colors
+    client.glyph_mint(&hash, &u1_address, &None, &u1_colors, &None);
+    client.glyph_contributor_set(&hash, &u2_address, &true);
+    client.glyph_mint(&hash, &u2_address, &None, &u2_colors, &None);
+    client.colors_set_operator(&u3_address, &u1_address, &100);
+    client.glyph_mint_from(&hash, &u1_address, &u3_address, &None, &u3_colors, &None);
+
+    assert_eq!(client.color_balance_total(&u2_address, &1), 0);
+    assert_eq!(client.color_balance_total(&u3_address, &2), 0);
+
+    client.glyph_mint_cancel(&hash);
+
+    // Every color goes back to whoever spent it rather than to the owner
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 1);
+    assert_eq!(client.color_balance(&u2_address, &1, &None), 2);
+    assert_eq!(client.color_balance(&u3_address, &2, &None), 1);
+    assert_eq!(client.color_balance_total(&u1_address, &1), 0);
+    assert_eq!(client.color_balance_total(&u1_address, &2), 0);
+    assert_eq!(client.try_owner_of(&hash), Err(Ok(Error::NotFound)));
+
+    // Contributors go with the build, a fresh one at the same hash starts without them
+    assert!(client.glyph_contributors(&hash).is_empty());
+}
+
+#[test]
+fn test_glyph_status() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // One color payment per call
+    client.update(
+        &None,
+        &None,
+        &None,
+        &None,
+        &Some(COLOR_PAYMENT_ENTRIES),
+        &None,
+        &None,
+        &None,
+    );
+
+    client.colors_mine(&u1_address, &map![&env, (0, 2), (1, 1)], &None, &None, &None);
+
+    let colors = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![&env, (0, vec![&env, 0, 2]), (1, vec![&env, 1])]
+        )
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 3);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Unknown);
+
+    // A build has to start with some pixels or it would read as scraped
+    assert_eq!(
+        client.try_glyph_mint(&hash, &u1_address, &None, &map![&env], &None),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &None);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Building(3));
+    assert_eq!(client.try_owner_of(&hash), Err(Ok(Error::NotMinted)));
+
+    client.glyph_mint(&hash, &u1_address, &None, &map![&env], &Some(3));
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Minted);
+
+    client.glyph_scrape(&None, &hash);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(1, 1));
+    assert_eq!(client.try_owner_of(&hash), Err(Ok(Error::NotMinted)));
+
+    client.glyph_scrape(&None, &hash);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraped);
+    assert_eq!(client.try_owner_of(&hash), Err(Ok(Error::NotFound)));
+
+    // Building again starts from a clean slate
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &None);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Building(3));
+}
+
+#[test]
+fn test_scrape_rejections() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 2)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 1])])];
+    let (hash, _) = glyph_hash(&env, &colors, 2);
+
+    assert_eq!(
+        client.try_glyph_scrape(&None, &hash),
+        Err(Ok(Error::NotFound))
+    );
+
+    // Pending builds are cancelled, not scraped
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &None);
+
+    assert_eq!(
+        client.try_glyph_scrape(&None, &hash),
+        Err(Ok(Error::NotMinted))
+    );
+
+    client.glyph_mint(&hash, &u1_address, &None, &map![&env], &Some(2));
+    client.glyph_scrape(&None, &hash);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraped);
+    assert_eq!(
+        client.try_glyph_scrape(&None, &hash),
+        Err(Ok(Error::AlreadyScraped))
+    );
+}
+
+#[test]
+fn test_repaint() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(
+        &u1_address,
+        &map![&env, (0, 1), (1, 1), (2, 1), (3, 1)],
+        &None,
+        &None,
+        &None,
+    );
+    client.colors_mine(
+        &u2_address,
+        &map![&env, (0, 1), (1, 1), (3, 1)],
+        &Some(u1_address.clone()),
+        &None,
+        &None,
+    );
+
+    let colors = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![
+                &env,
+                (0, vec![&env, 0]),
+                (1, vec![&env, 1]),
+                (2, vec![&env, 2])
+            ]
+        )
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 3);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(3));
+
+    let repainted = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![
+                &env,
+                (0, vec![&env, 0]),
+                (1, vec![&env, 1]),
+                (3, vec![&env, 2])
+            ]
+        )
+    ];
+    let (repainted_hash, _) = glyph_hash(&env, &repainted, 3);
+
+    // Someone else minted and scraped the repainted glyph first
+    client.glyph_mint(&repainted_hash, &u2_address, &None, &repainted, &Some(3));
+    client.glyph_scrape(&None, &repainted_hash);
+
+    // Painting needs the colors to paint with
+    assert_eq!(
+        client.try_glyph_repaint(
+            &hash,
+            &map![&env, (u1_address.clone(), map![&env, (2, vec![&env, 2])])],
+            &map![&env, (u1_address.clone(), map![&env, (4, vec![&env, 2])])],
+            &3,
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    let new_hash = client.glyph_repaint(
+        &hash,
+        &map![&env, (u1_address.clone(), map![&env, (2, vec![&env, 2])])],
+        &map![&env, (u1_address.clone(), map![&env, (3, vec![&env, 2])])],
+        &3,
+    );
+
+    assert_eq!(new_hash, repainted_hash);
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Unknown);
+    assert_eq!(client.glyph_status(&new_hash), GlyphStatus::Minted);
+    assert_eq!(client.owner_of(&new_hash), u1_address);
+    assert_eq!(
+        client.glyphs_of(&u1_address, &0, &10),
+        vec![&env, new_hash.clone()]
+    );
+    assert_eq!(client.color_balance_total(&u1_address, &2), 1);
+    assert_eq!(client.color_balance_total(&u1_address, &3), 0);
+
+    env.as_contract(&contract_address, || {
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get::<StorageKey, Address>(&StorageKey::GlyphMinter(new_hash.clone())),
+            Some(u1_address.clone())
+        );
+    });
+}
+
+#[test]
+fn test_mint_variant() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(
+        &u1_address,
+        &map![&env, (0, 2), (1, 2), (2, 2), (3, 1)],
+        &None,
+        &None,
+        &None,
+    );
+    client.colors_mine(
+        &u2_address,
+        &map![&env, (0xFFFFFF, 1), (0xFFFFFE, 1), (0xFFFFFD, 1)],
+        &Some(u1_address.clone()),
+        &None,
+        &None,
+    );
+
+    // A 2x2 glyph with its bottom left pixel missing
+    let colors = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![
+                &env,
+                (0, vec![&env, 0]),
+                (1, vec![&env, 1]),
+                (2, vec![&env, 3])
+            ]
+        )
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 2);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(2));
+
+    let rotated = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![
+                &env,
+                (0, vec![&env, 1]),
+                (1, vec![&env, 3]),
+                (2, vec![&env, 2])
+            ]
+        )
+    ];
+    let (rotated_hash, _) = glyph_hash(&env, &rotated, 2);
+
+    assert_eq!(
+        client.glyph_mint_variant(&u1_address, &None, &hash, &Transform::Rotate90),
+        rotated_hash
+    );
+    assert_eq!(client.color_balance_total(&u1_address, &0), 0);
+    assert_eq!(client.glyph_parent(&rotated_hash), Some(hash.clone()));
+
+    let inverted = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![
+                &env,
+                (0xFFFFFF, vec![&env, 0]),
+                (0xFFFFFE, vec![&env, 1]),
+                (0xFFFFFD, vec![&env, 3])
+            ]
+        )
+    ];
+    let (inverted_hash, _) = glyph_hash(&env, &inverted, 2);
+
+    assert_eq!(
+        client.glyph_mint_variant(&u2_address, &None, &hash, &Transform::Invert),
+        inverted_hash
+    );
+    assert_eq!(client.owner_of(&inverted_hash), u2_address);
+    assert_eq!(
+        client.glyph_children(&hash, &0, &10),
+        vec![&env, rotated_hash.clone(), inverted_hash.clone()]
+    );
+
+    // Repainting a variant swaps it out of its parent's children
+    let repainted_hash = client.glyph_repaint(
+        &rotated_hash,
+        &map![&env, (u1_address.clone(), map![&env, (2, vec![&env, 2])])],
+        &map![&env, (u1_address.clone(), map![&env, (3, vec![&env, 2])])],
+        &2,
+    );
+
+    assert_eq!(client.glyph_parent(&rotated_hash), None);
+    assert_eq!(client.glyph_parent(&repainted_hash), Some(hash.clone()));
+    assert_eq!(
+        client.glyph_children(&hash, &0, &10),
+        vec![&env, inverted_hash, repainted_hash]
+    );
+
+    // Flipping a short last row would land pixels outside the bitmap
+    client.colors_mine(&u1_address, &map![&env, (4, 2)], &None, &None, &None);
+
+    let tall = map![
+        &env,
+        (u1_address.clone(), map![&env, (4, vec![&env, 1596, 1599])])
+    ];
+    let (tall_hash, _) = glyph_hash(&env, &tall, 7);
+
+    client.glyph_mint(&tall_hash, &u1_address, &None, &tall, &Some(7));
+    client.colors_mine(&u1_address, &map![&env, (4, 2)], &None, &None, &None);
+
+    assert_eq!(
+        client.try_glyph_mint_variant(&u1_address, &None, &tall_hash, &Transform::FlipH),
+        Err(Ok(Error::NotPermitted))
+    );
+}
+
+#[test]
+fn test_mine_and_mint() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &3);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1), (1, 5)], &None, &None, &None);
+
+    let colors = map![
+        &env,
+        (
+            u1_address.clone(),
+            map![&env, (0, vec![&env, 0, 1]), (1, vec![&env, 2]), (2, vec![&env, 3])]
+        )
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 2);
+
+    // One more of color 0 and one of color 2 at 3 apiece
+    assert_eq!(
+        client.glyph_mint_quote(&u1_address, &colors, &2),
+        MintQuote {
+            hash: hash.clone(),
+            shortfall: vec![&env, (u1_address.clone(), 0, 1), (u1_address.clone(), 2, 1)],
+            cost: 6,
+            miners: 1,
+        }
+    );
+    assert_eq!(
+        client.try_glyph_mint_quote(&u1_address, &colors, &0),
+        Err(Ok(Error::MissingWidth))
+    );
+    assert_eq!(
+        client.try_glyph_mint_quote(
+            &u1_address,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 1600])])],
+            &2
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        client.try_glyph_mine_and_mint(&hash, &u1_address, &None, &colors, &Some(2), &Some(5)),
+        Err(Ok(Error::MaxCostExceeded))
+    );
+    assert_eq!(
+        client.glyph_mine_and_mint(&hash, &u1_address, &None, &colors, &Some(2), &Some(6)),
+        6
+    );
+    assert_eq!(token_client.balance(&u1_address), 10_000 - 18 - 6);
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Minted);
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 0);
+    assert_eq!(client.color_balance(&u1_address, &1, &None), 4);
+    assert_eq!(client.color_balance(&u1_address, &2, &None), 0);
+    assert_eq!(
+        client.try_glyph_mint_quote(&u1_address, &colors, &2),
+        Err(Ok(Error::NotEmpty))
+    );
+}
+
+#[test]
+fn test_reserve() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 2)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (1, 2)], &None, &None, &None);
+
+    env.ledger().set_sequence_number(1_000);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 1])])];
+    let (hash, _) = glyph_hash(&env, &colors, 2);
+    let salt = BytesN::<32>::random(&env);
+
+    let mut preimage: Bytes = hash.clone().into();
+    preimage.append(&salt.clone().into());
+    let commitment = env.crypto().sha256(&preimage).to_bytes();
+
+    // Reservations can't already be over nor run past a day
+    assert_eq!(
+        client.try_glyph_reserve(&u1_address, &commitment, &999),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        client.try_glyph_reserve(
+            &u1_address,
+            &commitment,
+            &(1_000 + MAX_RESERVATION_LEDGERS + 1)
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    client.glyph_reserve(&u1_address, &commitment, &(1_000 + MAX_RESERVATION_LEDGERS));
+
+    // Commitments only open in a later ledger than the one they were made in
+    assert_eq!(
+        client.try_glyph_reveal(&u1_address, &hash, &salt),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    env.ledger().set_sequence_number(1_001);
+
+    // Only the reserver can open their commitment, and only with the right salt
+    assert_eq!(
+        client.try_glyph_reveal(&u2_address, &hash, &salt),
+        Err(Ok(Error::NotFound))
+    );
+    assert_eq!(
+        client.try_glyph_reveal(&u1_address, &hash, &BytesN::random(&env)),
+        Err(Ok(Error::NotFound))
+    );
+
+    client.glyph_reveal(&u1_address, &hash, &salt);
+
+    assert_eq!(client.glyph_reservation(&hash), Some(u1_address.clone()));
+
+    assert_eq!(
+        client.try_glyph_mint(&hash, &u2_address, &None, &colors, &Some(2)),
+        Err(Ok(Error::Reserved))
+    );
+    assert_eq!(
+        client.try_glyph_mint_quote(&u2_address, &colors, &2),
+        Err(Ok(Error::Reserved))
+    );
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(2));
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Minted);
+    assert_eq!(client.glyph_reservation(&hash), None);
+
+    // Once a reservation runs out anyone can mint the hash
+    let colors = map![&env, (u2_address.clone(), map![&env, (1, vec![&env, 0, 1])])];
+    let (hash, _) = glyph_hash(&env, &colors, 2);
+
+    let mut preimage: Bytes = hash.clone().into();
+    preimage.append(&salt.clone().into());
+    let commitment = env.crypto().sha256(&preimage).to_bytes();
+
+    client.glyph_reserve(&u1_address, &commitment, &1_010);
+
+    env.ledger().set_sequence_number(1_002);
+
+    client.glyph_reveal(&u1_address, &hash, &salt);
+
+    assert_eq!(
+        client.try_glyph_mint(&hash, &u2_address, &None, &colors, &Some(2)),
+        Err(Ok(Error::Reserved))
+    );
+
+    env.ledger().set_sequence_number(1_011);
+
+    assert_eq!(client.glyph_reservation(&hash), None);
+
+    client.glyph_mint(&hash, &u2_address, &None, &colors, &Some(2));
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+}
+
+#[test]
+fn test_animate() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 2), (0xFF0000, 2)], &None, &None, &None);
+
+    let frame_1 = map![
+        &env,
+        (u1_address.clone(), map![&env, (0, vec![&env, 0]), (0xFF0000, vec![&env, 1])])
+    ];
+    let frame_2 = map![
+        &env,
+        (u1_address.clone(), map![&env, (0, vec![&env, 1]), (0xFF0000, vec![&env, 0])])
+    ];
+    let (frame_1_hash, _) = glyph_hash(&env, &frame_1, 2);
+    let (frame_2_hash, _) = glyph_hash(&env, &frame_2, 2);
+
+    client.glyph_mint(&frame_1_hash, &u1_address, &None, &frame_1, &Some(2));
+    client.glyph_mint(&frame_2_hash, &u1_address, &None, &frame_2, &Some(2));
+
+    let frames = vec![
+        &env,
+        (frame_1_hash.clone(), 500),
+        (frame_2_hash.clone(), 500),
+        (frame_1_hash.clone(), 1000),
+    ];
+
+    // Frames have to belong to the minter
+    assert_eq!(
+        client.try_glyph_animate(&u2_address, &None, &frames),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    let hash = client.glyph_animate(&u1_address, &Some(u2_address.clone()), &frames);
+
+    // The frames are already counted, bundling them doesn't mint anything new
+    assert_eq!(client.stats().glyphs_minted, 2);
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Animated);
+    assert_eq!(client.glyph_animation(&hash), frames);
+    assert_eq!(client.owner_of(&hash), u2_address);
+    assert_eq!(client.owner_of(&frame_1_hash), contract_address);
+    assert_eq!(client.owner_of(&frame_2_hash), contract_address);
+
+    let svg = client.glyph_animation_svg(&hash);
+
+    assert!(svg.slice(..4) == Bytes::from_slice(&env, b"<svg"));
+    assert!(svg.len() > 4);
+
+    client.glyph_unanimate(&hash);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Unknown);
+    assert_eq!(client.owner_of(&frame_1_hash), u2_address);
+    assert_eq!(client.owner_of(&frame_2_hash), u2_address);
+}
+
+#[test]
+fn test_compose() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 4), (0xFF0000, 1)], &None, &None, &None);
+
+    let square = map![
+        &env,
+        (u1_address.clone(), map![&env, (0, vec![&env, 0, 1, 2, 3])])
+    ];
+    let dot = map![
+        &env,
+        (u1_address.clone(), map![&env, (0xFF0000, vec![&env, 0])])
+    ];
+    let (square_hash, _) = glyph_hash(&env, &square, 2);
+    let (dot_hash, _) = glyph_hash(&env, &dot, 1);
+
+    client.glyph_mint(&square_hash, &u1_address, &None, &square, &Some(2));
+    client.glyph_mint(&dot_hash, &u1_address, &None, &dot, &Some(1));
+
+    // The dot sits over the square's bottom right pixel and again off to its right
+    let layers = vec![
+        &env,
+        Layer {
+            hash: dot_hash.clone(),
+            x: 1,
+            y: 1,
+            z: 1,
+        },
+        Layer {
+            hash: square_hash.clone(),
+            x: 0,
+            y: 0,
+            z: 0,
+        },
+        Layer {
+            hash: dot_hash.clone(),
+            x: 2,
+            y: 0,
+            z: 0,
+        },
+    ];
+
+    let hash = client.glyph_compose(&u1_address, &None, &layers);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Composite);
+    assert_eq!(client.glyph_composite(&hash), layers);
+    assert_eq!(client.owner_of(&hash), u1_address);
+    assert_eq!(client.owner_of(&square_hash), contract_address);
+    assert_eq!(client.owner_of(&dot_hash), contract_address);
+    assert_eq!(
+        client.glyph_composite_bitmap(&hash),
+        Bitmap {
+            width: 3,
+            height: 2,
+            pixels: map![
+                &env,
+                (0, 0),
+                (1, 0),
+                (2, 0xFF0000),
+                (3, 0),
+                (4, 0xFF0000)
+            ],
+        }
+    );
+
+    client.glyph_decompose(&hash);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Unknown);
+    assert_eq!(client.owner_of(&square_hash), u1_address);
+    assert_eq!(client.owner_of(&dot_hash), u1_address);
+}
+
+#[test]
+fn test_owner_index() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(
+        &u1_address,
+        &map![&env, (0, 1), (1, 1), (2, 1)],
+        &None,
+        &None,
+        &None,
+    );
+
+    let mut hashes = vec![&env];
+
+    for color in 0..3u32 {
+        let colors = map![
+            &env,
+            (u1_address.clone(), map![&env, (color, vec![&env, 0])])
+        ];
+        let (hash, _) = glyph_hash(&env, &colors, 1);
+
+        client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(1));
+
+        hashes.push_back(hash);
+    }
+
+    assert_eq!(client.glyph_count_of(&u1_address), 3);
+    assert_eq!(
+        client.color_supply(&0),
+        ColorSupply {
+            mined: 1,
+            burned: 0,
+            locked: 1,
+            wallets: 0
+        }
+    );
+    assert_eq!(client.glyphs_of(&u1_address, &0, &10), hashes);
+    assert_eq!(client.glyphs_of(&u1_address, &1, &1).len(), 1);
+
+    client.glyph_transfer(&u1_address, &u2_address, &hashes.get(0).unwrap());
+
+    assert_eq!(client.glyph_count_of(&u1_address), 2);
+    assert_eq!(
+        client.glyphs_of(&u2_address, &0, &10),
+        vec![&env, hashes.get(0).unwrap()]
+    );
+
+    client.glyph_scrape(&None, &hashes.get(1).unwrap());
+
+    assert_eq!(client.color_supply(&1).wallets, 1);
+    assert_eq!(client.stats().glyphs_minted, 3);
+    assert_eq!(client.stats().glyphs_scraped, 1);
+
+    assert_eq!(
+        client.glyphs_of(&u1_address, &0, &10),
+        vec![&env, hashes.get(2).unwrap()]
+    );
+}
+
+#[test]
+fn test_glyph_transfer() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1), (1, 1)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let (hash, _) = glyph_hash(&env, &colors, 1);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(1));
+
+    let pending_colors = map![&env, (u1_address.clone(), map![&env, (1, vec![&env, 0])])];
+    let (pending_hash, _) = glyph_hash(&env, &pending_colors, 1);
+
+    client.glyph_mint(&pending_hash, &u1_address, &None, &pending_colors, &None);
+
+    // `from` has to be the current owner
+    assert_eq!(
+        client.try_glyph_transfer(&u2_address, &u3_address, &hash),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    assert_eq!(
+        client.try_glyph_transfer(&u1_address, &u1_address, &hash),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    assert_eq!(
+        client.try_glyph_transfer(&u1_address, &u2_address, &pending_hash),
+        Err(Ok(Error::NotMinted))
+    );
+
+    assert_eq!(
+        client.try_glyph_transfer(&u1_address, &u2_address, &BytesN::random(&env)),
+        Err(Ok(Error::NotFound))
+    );
+
+    client.glyph_transfer(&u1_address, &u2_address, &hash);
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+    assert_eq!(
+        client.try_glyph_transfer(&u1_address, &u3_address, &hash),
+        Err(Ok(Error::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_nft_interface() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let (hash, _) = glyph_hash(&env, &colors, 1);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(1));
+
+    assert_eq!(client.name(), String::from_str(&env, "Colorglyph"));
+    assert_eq!(client.symbol(), String::from_str(&env, "GLYPH"));
+    assert_eq!(client.balance(&u1_address), 1);
+    assert_eq!(client.owner_of(&hash), u1_address);
+
+    assert_eq!(
+        client.try_approve(&u2_address, &u2_address, &hash, &100),
+        Err(Ok(Error::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_approve(&u1_address, &u2_address, &BytesN::random(&env), &100),
+        Err(Ok(Error::NotFound))
+    );
+
+    client.approve(&u1_address, &u2_address, &hash, &100);
+
+    assert_eq!(client.get_approved(&hash), Some(u2_address.clone()));
+
+    client.transfer(&u1_address, &u2_address, &hash);
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+    assert_eq!(client.balance(&u1_address), 0);
+    assert_eq!(client.get_approved(&hash), None);
+
+    client.set_approval_for_all(&u2_address, &u1_address, &100);
+
+    assert!(client.is_approved_for_all(&u2_address, &u1_address));
+    assert!(!client.is_approved_for_all(&u1_address, &u2_address));
+
+    client.base_uri_set(&String::from_str(&env, "https://example.com/glyph/"));
+
+    assert_eq!(
+        client.token_uri(&hash),
+        String::from_str(
+            &env,
+            "https://example.com/glyph/b40711a88c7039756fb8a73827eabe2c0fe5a0346ca7e0a104adc0fc764f528d"
+        )
+    );
+
+    // Only minted glyphs have a URI
+    client.colors_mine(&u1_address, &map![&env, (1, 1)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (1, vec![&env, 0])])];
+    let (building_hash, _) = glyph_hash(&env, &colors, 1);
+
+    client.glyph_mint(&building_hash, &u1_address, &None, &colors, &None);
+
+    assert_eq!(
+        client.try_token_uri(&building_hash),
+        Err(Ok(Error::NotMinted))
+    );
+    assert_eq!(
+        client.try_token_uri(&BytesN::random(&env)),
+        Err(Ok(Error::NotFound))
+    );
+}
+
+mod receiver {
+    use crate::GlyphReceiver;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Bytes, BytesN, Env};
+
+    #[contract]
+    pub struct GlyphVault;
+
+    #[contractimpl]
+    impl GlyphReceiver for GlyphVault {
+        fn on_glyph_received(
+            env: Env,
+            _operator: Address,
+            _from: Address,
+            hash: BytesN<32>,
+            _data: Bytes,
+        ) -> bool {
+            env.storage().instance().set(&symbol_short!("last"), &hash);
+
+            true
+        }
+    }
+
+    #[contractimpl]
+    impl GlyphVault {
+        pub fn last(env: Env) -> Option<BytesN<32>> {
+            env.storage().instance().get(&symbol_short!("last"))
+        }
+    }
+}
+
+mod refuser {
+    use crate::GlyphReceiver;
+    use soroban_sdk::{contract, contractimpl, Address, Bytes, BytesN, Env};
+
+    #[contract]
+    pub struct GlyphRefuser;
+
+    #[contractimpl]
+    impl GlyphReceiver for GlyphRefuser {
+        fn on_glyph_received(
+            _env: Env,
+            _operator: Address,
+            _from: Address,
+            _hash: BytesN<32>,
+            _data: Bytes,
+        ) -> bool {
+            false
+        }
+    }
+}
+
+#[test]
+fn test_safe_transfer() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let vault_address = env.register_contract(None, receiver::GlyphVault);
+    let vault_client = receiver::GlyphVaultClient::new(&env, &vault_address);
+    let refuser_address = env.register_contract(None, refuser::GlyphRefuser);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::from_string(&String::from_str(
+        &env,
+        "GC5HBZMF4QX475QYOJ7XB5VZZZH3DP6N6X2ENJBD7OY7VTZ265YLHAZR",
+    ));
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let (hash, _) = glyph_hash(&env, &colors, 1);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(1));
+
+    // Accounts aren't called back, calling into one would fail the transfer
+    client.safe_transfer(&u1_address, &u2_address, &hash, &Bytes::new(&env));
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+
+    // A contract that refuses the glyph reverts the transfer
+    assert_eq!(
+        client.try_safe_transfer(&u2_address, &refuser_address, &hash, &Bytes::new(&env)),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(client.owner_of(&hash), u2_address);
+
+    client.safe_transfer(&u2_address, &vault_address, &hash, &Bytes::new(&env));
+
+    assert_eq!(client.owner_of(&hash), vault_address);
+    assert_eq!(vault_client.last(), Some(hash));
+}
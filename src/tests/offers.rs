@@ -8,7 +8,8 @@ use soroban_sdk::{map, testutils::Address as _, token, vec, Address, BytesN, Env
 
 use crate::{
     contract::{ColorGlyph, ColorGlyphClient},
-    types::{Error, Offer, StorageKey},
+    glyphs::glyph_hash,
+    types::{Error, GlyphStatus, Offer, StorageKey},
 };
 
 const ITERS: i128 = 10i128;
@@ -50,7 +51,7 @@ fn test_self_purchase() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = BytesN::from_array(
         &env,
@@ -74,14 +75,14 @@ fn test_self_purchase() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u1_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&glyph, &asset, &None);
+    client.offer_post(&asset_sell, &glyph, &None);
 
-    client.offer_post(&glyph, &glyph);
+    client.offer_post(&glyph, &glyph, &None);
 
     client.offers_get(&glyph, &Some(glyph.clone()));
 
-    client.offer_post(&glyph, &glyph.clone());
+    client.offer_post(&glyph, &glyph.clone(), &None);
 
     assert_eq!(
         client.try_offers_get(&glyph, &Some(glyph.clone())),
@@ -128,7 +129,7 @@ fn test_sell_scrape_buy() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = BytesN::from_array(
         &env,
@@ -152,10 +153,17 @@ fn test_sell_scrape_buy() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     client.glyph_scrape(&None, &hash.clone());
 
+    // 10 colors at 3 color payments per call
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(7, 3));
+
+    for _ in 0..3 {
+        client.glyph_scrape(&None, &hash.clone());
+    }
+
     // assert_eq!(
     //     client.try_glyph_get(&HashType::Colors(u1_address.clone())),
     //     Err(Ok(Error::NotFound))
@@ -168,7 +176,7 @@ fn test_sell_scrape_buy() {
         Err(Ok(Error::NotFound))
     );
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     client.offers_get(&asset, &Some(glyph.clone()));
 
@@ -180,7 +188,7 @@ fn test_sell_scrape_buy() {
         &Some(16),
     );
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
@@ -240,7 +248,7 @@ fn test_dupe() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = BytesN::from_array(
         &env,
@@ -264,19 +272,19 @@ fn test_dupe() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     assert_eq!(
-        client.try_offer_post(&glyph, &asset),
+        client.try_offer_post(&glyph, &asset, &None),
         Err(Ok(Error::NotEmpty))
     );
 
-    client.offer_delete(&glyph, &None); // <- delete all open glyph sell offers
+    client.offer_delete(&glyph, &None, &None); // <- delete all open glyph sell offers
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     assert_eq!(
-        client.try_offer_post(&asset_sell, &glyph),
+        client.try_offer_post(&asset_sell, &glyph, &None),
         Err(Ok(Error::NotEmpty))
     );
 }
@@ -321,7 +329,13 @@ fn test_buy_glyph() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u3_address, &color_amount, &None, &Some(u1_address.clone()));
+    client.colors_mine(
+        &u3_address,
+        &color_amount,
+        &None,
+        &Some(u1_address.clone()),
+        &None,
+    );
 
     // println!("{:?}\n", colors_indexes);
 
@@ -351,7 +365,7 @@ fn test_buy_glyph() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
     client.offers_get(&asset_sell, &Some(glyph.clone())); // User 2 is selling
     client.offers_get(&asset, &Some(glyph.clone())); // Someone is selling
 
@@ -364,7 +378,7 @@ fn test_buy_glyph() {
         Err(Ok(Error::NotFound))
     );
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     // env.budget().print();
 
@@ -393,6 +407,8 @@ fn test_buy_glyph() {
     assert_eq!(token_client.balance(&u1_address), 10_098i128);
     assert_eq!(token_client.balance(&u2_address), 9_900i128);
     assert_eq!(token_client.balance(&u3_address), 9_992i128);
+
+    assert_eq!(client.stats().sales, 1);
 }
 
 #[test]
@@ -436,7 +452,13 @@ fn test_sell_glyph() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u3_address, &color_amount, &None, &Some(u1_address.clone()));
+    client.colors_mine(
+        &u3_address,
+        &color_amount,
+        &None,
+        &Some(u1_address.clone()),
+        &None,
+    );
 
     let hash = BytesN::from_array(
         &env,
@@ -464,9 +486,9 @@ fn test_sell_glyph() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     env.as_contract(&contract_address, || {
         let res = env
@@ -539,7 +561,7 @@ fn test_swap_glyph() {
         colors_b_amount.set(hex_b as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &colors_a_amount, &None, &None);
+    client.colors_mine(&u1_address, &colors_a_amount, &None, &None, &None);
 
     let hash_a = BytesN::from_array(
         &env,
@@ -559,7 +581,7 @@ fn test_swap_glyph() {
 
     client.glyph_mint(&hash_a, &u1_address, &None, &map![&env], &Some(16));
 
-    client.colors_mine(&u2_address, &colors_b_amount, &None, &None);
+    client.colors_mine(&u2_address, &colors_b_amount, &None, &None, &None);
 
     let hash_b = BytesN::from_array(
         &env,
@@ -585,9 +607,9 @@ fn test_swap_glyph() {
     let glyph_1 = Offer::Glyph(hash_a.clone());
     let glyph_2 = Offer::Glyph(hash_b.clone());
 
-    client.offer_post(&glyph_1, &glyph_2);
+    client.offer_post(&glyph_1, &glyph_2, &None);
 
-    client.offer_post(&glyph_2, &glyph_1);
+    client.offer_post(&glyph_2, &glyph_1, &None);
 
     env.as_contract(&contract_address, || {
         let res_a = env
@@ -655,7 +677,7 @@ fn test_rm_glyph_buy() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = BytesN::from_array(
         &env,
@@ -683,7 +705,7 @@ fn test_rm_glyph_buy() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u1_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     assert_eq!(token_client.balance(&contract_address), 1i128);
 
@@ -691,7 +713,7 @@ fn test_rm_glyph_buy() {
 
     client.offers_get(&asset, &Some(glyph.clone()));
 
-    client.offer_delete(&asset_sell, &Some(glyph.clone()));
+    client.offer_delete(&asset_sell, &Some(glyph.clone()), &None);
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
@@ -740,7 +762,7 @@ fn test_rm_glyph_sell() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = BytesN::from_array(
         &env,
@@ -766,11 +788,11 @@ fn test_rm_glyph_sell() {
     let glyph = Offer::Glyph(hash.clone());
     let asset = Offer::Asset(token_address.clone(), 1i128);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     client.offers_get(&glyph, &Some(asset.clone()));
 
-    client.offer_delete(&glyph, &Some(asset.clone()));
+    client.offer_delete(&glyph, &Some(asset.clone()), &None);
 
     assert_eq!(
         client.try_offers_get(&glyph, &Some(asset.clone())),
@@ -826,7 +848,7 @@ fn test_rm_glyph_swap() {
         colors_b_amount.set(hex_b as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &colors_a_amount, &None, &None);
+    client.colors_mine(&u1_address, &colors_a_amount, &None, &None, &None);
 
     let hash_a = BytesN::from_array(
         &env,
@@ -851,6 +873,7 @@ fn test_rm_glyph_swap() {
         &colors_b_amount,
         &None,
         &Some(u2_address.clone()),
+        &None,
     );
 
     let hash_b = BytesN::from_array(
@@ -877,11 +900,11 @@ fn test_rm_glyph_swap() {
     let glyph_a = Offer::Glyph(hash_a.clone());
     let glyph_b = Offer::Glyph(hash_b.clone());
 
-    client.offer_post(&glyph_a, &glyph_b);
+    client.offer_post(&glyph_a, &glyph_b, &None);
 
     client.offers_get(&glyph_a, &Some(glyph_b.clone()));
 
-    client.offer_delete(&glyph_a, &Some(glyph_b.clone()));
+    client.offer_delete(&glyph_a, &Some(glyph_b.clone()), &None);
 
     assert_eq!(
         client.try_offers_get(&glyph_a, &Some(glyph_b)),
@@ -892,3 +915,409 @@ fn test_rm_glyph_swap() {
 
     assert_eq!(token_client.balance(&u1_address), 9980i128);
 }
+
+#[test]
+fn test_operator_listing() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let market_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let (hash, _) = glyph_hash(&env, &colors, 1);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(1));
+
+    let amount: i128 = 100;
+    let glyph = Offer::Glyph(hash.clone());
+    let asset = Offer::Asset(token_address.clone(), amount);
+    let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
+
+    // The marketplace lists on the owner's behalf
+    client.set_approval_for_all(&u1_address, &market_address, &100);
+    client.offer_post(&glyph, &asset, &Some(market_address.clone()));
+    client.offer_post(&asset_sell, &glyph, &None);
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+    assert_eq!(token_client.balance(&u1_address), 10_099);
+    assert_eq!(token_client.balance(&market_address), 0);
+
+    // A single glyph approval lets a custodian move it
+    client.approve(&u2_address, &market_address, &hash, &100);
+    client.transfer_from(&market_address, &u2_address, &u3_address, &hash);
+
+    assert_eq!(client.owner_of(&hash), u3_address);
+    assert_eq!(client.get_approved(&hash), None);
+}
+
+#[test]
+fn test_unminted_listing() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 2)], &None, &None, &None);
+
+    let colors = map![
+        &env,
+        (u1_address.clone(), map![&env, (0, vec![&env, 0, 1])])
+    ];
+    let (hash, _) = glyph_hash(&env, &colors, 2);
+
+    // Still building
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &None);
+
+    let amount: i128 = 100;
+    let glyph = Offer::Glyph(hash.clone());
+    let asset = Offer::Asset(token_address.clone(), amount);
+    let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
+
+    assert_eq!(
+        client.try_offer_post(&glyph, &asset, &None),
+        Err(Ok(Error::NotMinted))
+    );
+
+    // Bids on a glyph that isn't minted yet are still fine
+    client.offer_post(&asset_sell, &glyph, &None);
+
+    client.glyph_mint(&hash, &u1_address, &None, &Map::new(&env), &Some(2));
+    client.glyph_scrape(&None, &hash);
+
+    // Nor can a scraped glyph be sold into the standing bid
+    assert_eq!(
+        client.try_offer_post(&glyph, &asset, &None),
+        Err(Ok(Error::NotMinted))
+    );
+}
+
+#[test]
+fn test_scrape_refund() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+    token_admin_client.mint(&u3_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Two refunds per scrape, a call that still has bids to refund pays out no colors
+    client.update(&None, &None, &None, &None, &Some(2), &None, &None, &None);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let (hash, _) = glyph_hash(&env, &colors, 1);
+
+    client.glyph_mint(&hash, &u1_address, &None, &colors, &Some(1));
+
+    let glyph = Offer::Glyph(hash.clone());
+
+    client.offer_post(
+        &Offer::AssetSell(u2_address.clone(), token_address.clone(), 100),
+        &glyph,
+        &None,
+    );
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), token_address.clone(), 100),
+        &glyph,
+        &None,
+    );
+    client.offer_post(
+        &Offer::AssetSell(u2_address.clone(), token_address.clone(), 200),
+        &glyph,
+        &None,
+    );
+
+    assert_eq!(client.offers_stranded(&hash).len(), 0);
+
+    client.glyph_scrape(&None, &hash);
+
+    // One bid left to refund and then the color
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(1, 2));
+    assert_eq!(token_client.balance(&u2_address), 10_000 - 200);
+    assert_eq!(token_client.balance(&u3_address), 10_000);
+    assert_eq!(
+        client.offers_stranded(&hash),
+        vec![
+            &env,
+            Offer::AssetSell(u2_address.clone(), token_address.clone(), 200)
+        ]
+    );
+
+    // Bids still waiting on the scrape can be reclaimed in the meantime
+    client.offer_delete(
+        &Offer::AssetSell(u2_address.clone(), token_address.clone(), 200),
+        &Some(glyph.clone()),
+        &None,
+    );
+
+    assert_eq!(token_client.balance(&u2_address), 10_000);
+    assert_eq!(client.offers_stranded(&hash).len(), 0);
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(1, 1));
+
+    // A bid escrowed before its glyph's bids were indexed
+    token_admin_client.mint(&contract_address, &300);
+
+    env.as_contract(&contract_address, || {
+        env.storage().persistent().set(
+            &StorageKey::AssetOffer(hash.clone(), token_address.clone(), 300),
+            &vec![&env, u3_address.clone()],
+        );
+    });
+
+    assert_eq!(client.offers_stranded(&hash).len(), 0);
+
+    client.offers_reindex(
+        &hash,
+        &vec![
+            &env,
+            (token_address.clone(), 300),
+            (token_address.clone(), 400),
+        ],
+    );
+
+    assert_eq!(
+        client.offers_stranded(&hash),
+        vec![
+            &env,
+            Offer::AssetSell(u3_address.clone(), token_address.clone(), 300)
+        ]
+    );
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(1, 2));
+
+    client.glyph_scrape(&None, &hash);
+
+    assert_eq!(token_client.balance(&u3_address), 10_300);
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraping(1, 1));
+
+    client.glyph_scrape(&None, &hash);
+
+    assert_eq!(client.glyph_status(&hash), GlyphStatus::Scraped);
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 1);
+}
+
+#[test]
+fn test_contributor_royalties() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+    token_admin_client.mint(&u3_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (1, 3)], &None, &None, &None);
+
+    let u1_colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let u2_colors = map![
+        &env,
+        (u2_address.clone(), map![&env, (1, vec![&env, 1, 2, 3])])
+    ];
+    let (hash, _) = glyph_hash(
+        &env,
+        &map![
+            &env,
+            (u1_address.clone(), map![&env, (0, vec![&env, 0])]),
+            (u2_address.clone(), map![&env, (1, vec![&env, 1, 2, 3])])
+        ],
+        4,
+    );
+
+    client.glyph_mint(&hash, &u1_address, &None, &u1_colors, &None);
+    client.glyph_contributor_set(&hash, &u2_address, &true);
+    client.glyph_mint(&hash, &u2_address, &None, &u2_colors, &None);
+    client.glyph_mint(&hash, &u1_address, &None, &Map::new(&env), &Some(4));
+
+    assert_eq!(client.owner_of(&hash), u1_address);
+    assert_eq!(
+        client.glyph_contributors(&hash),
+        map![&env, (u2_address.clone(), 3)]
+    );
+
+    let glyph = Offer::Glyph(hash.clone());
+
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), token_address.clone(), 1_000),
+        &glyph,
+        &None,
+    );
+    client.offer_post(&glyph, &Offer::Asset(token_address.clone(), 1_000), &None);
+
+    // 3% minter royalty split 3:1 with the contributor, 2% miner royalty split by colors
+    assert_eq!(token_client.balance(&u2_address), 9_997 + 22 + 15);
+    assert_eq!(token_client.balance(&u1_address), 9_999 + 8 + 5 + 950);
+    assert_eq!(client.owner_of(&hash), u3_address);
+}
+
+#[test]
+fn test_derivative_royalties() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+    token_admin_client.mint(&u3_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address, &1);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (1, 1)], &None, &None, &None);
+
+    let parent_colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+    let (parent_hash, _) = glyph_hash(&env, &parent_colors, 1);
+
+    client.glyph_mint(&parent_hash, &u1_address, &None, &parent_colors, &Some(1));
+    client.glyph_derive_lock(&parent_hash, &true);
+
+    let child_colors = map![&env, (u2_address.clone(), map![&env, (1, vec![&env, 0])])];
+    let (child_hash, _) = glyph_hash(&env, &child_colors, 1);
+
+    client.glyph_mint(&child_hash, &u2_address, &None, &child_colors, &None);
+    client.glyph_parent_set(&child_hash, &parent_hash);
+
+    // The locked parent's owner signed off too
+    assert!(env
+        .auths()
+        .iter()
+        .any(|(address, _)| *address == u1_address));
+
+    client.glyph_mint(&child_hash, &u2_address, &None, &Map::new(&env), &Some(1));
+
+    assert_eq!(client.glyph_parent(&child_hash), Some(parent_hash.clone()));
+    assert_eq!(client.glyph_parent(&parent_hash), None);
+    assert_eq!(
+        client.glyph_children(&parent_hash, &0, &10),
+        vec![&env, child_hash.clone()]
+    );
+
+    // The 3% minter and 2% miner cuts leave at most 95% for derivatives
+    assert_eq!(
+        client.try_derivative_royalty_rate_set(&96),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        client.try_derivative_royalty_rate_set(&-1),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    client.derivative_royalty_rate_set(&2);
+
+    let glyph = Offer::Glyph(child_hash.clone());
+
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), token_address.clone(), 1_000),
+        &glyph,
+        &None,
+    );
+    client.offer_post(&glyph, &Offer::Asset(token_address.clone(), 1_000), &None);
+
+    // 2% of the sale goes to the parent's minter
+    assert_eq!(token_client.balance(&u1_address), 9_999 + 20);
+    assert_eq!(token_client.balance(&u2_address), 9_999 + 980);
+}
@@ -13,6 +13,10 @@ pub enum Error { // TODO Clean out unused errors
     MissingAddress = 7,
     MissingBuy = 8,
     NotInitialized = 9,
+    NotMinted = 10,
+    AlreadyScraped = 11,
+    Reserved = 12,
+    MaxCostExceeded = 13,
 }
 
 #[contracttype]
@@ -26,12 +30,44 @@ pub enum StorageKey {
     MineMultiplier,
     MinterRoyaltyRate,
     MinerRoyaltyRate,
+    Stats,
+    BaseUri,
+    CanvasCount,
+    DerivativeRoyaltyRate,
+    MinePricing,
     Color(Address, Address, u32), // (miner, owner, color) : amount 
     Glyph(BytesN<32>),
     GlyphOwner(BytesN<32>),
     GlyphMinter(BytesN<32>),
     GlyphOffer(BytesN<32>),
+    GlyphContributors(BytesN<32>), // hash : Map<contributor, pixels>
+    GlyphSpends(BytesN<32>),       // hash : Map<(spender, miner, color), amount> spent into a pending build
+    GlyphParent(BytesN<32>),
+    GlyphChildren(BytesN<32>, u32), // (parent, page) : Vec<BytesN<32>>
+    GlyphChildCount(BytesN<32>),
+    GlyphChildIndex(BytesN<32>),   // child : position in its parent's pages
+    GlyphDeriveLocked(BytesN<32>), // parent : derivatives need the parent owner's sign off
+    Animation(BytesN<32>),         // hash : Vec<(frame hash, delay ms)>
+    Composite(BytesN<32>),         // hash : Vec<Layer>
+    ReservationCommitment(Address, BytesN<32>), // (reserver, sha256(hash ++ salt)) : (committed ledger, live until ledger)
+    GlyphReservation(BytesN<32>),  // hash : GlyphReservation
     AssetOffer(BytesN<32>, Address, i128), // (hash, sac, amount) : Vec<Address>
+    AssetOfferKeys(BytesN<32>),            // hash : Vec<(sac, amount)> with open bids
+    OwnerGlyphs(Address, u32),             // (owner, page) : Vec<BytesN<32>>
+    OwnerGlyphCount(Address),
+    OwnerGlyphIndex(BytesN<32>), // hash : position in the owner's pages
+    OwnerColors(Address, u32),   // (owner, page) : Vec<(miner, color)>
+    OwnerColorCount(Address),
+    OwnerColorIndex(Address, Address, u32), // (owner, miner, color) : position in the owner's pages
+    ColorTotal(Address, u32),               // (owner, color) : amount summed across miners
+    ColorSupply(u32),
+    ColorAllowance(Address, Address, Address, u32), // (owner, spender, miner, color) : ColorAllowance
+    ColorOperator(Address, Address),                // (owner, operator) : live until ledger
+    GlyphApproval(BytesN<32>),                      // hash : GlyphApproval
+    GlyphOperator(Address, Address),                // (owner, operator) : live until ledger
+    Canvas(u32),
+    CanvasChunk(u32, u32),         // (canvas, chunk) : Map<index, CanvasPixel>
+    CanvasSnapshot(BytesN<32>),    // hash : canvas the glyph was frozen from
 }
 
 #[contracttype]
@@ -42,6 +78,135 @@ pub struct Glyph {
     pub colors: Map<Address, Map<u32, Vec<u32>>>,
 }
 
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriceCurve {
+    Linear,      // mine_multiplier + rate * mined
+    Exponential, // mine_multiplier doubled every `rate` mined
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinePricing {
+    pub curve: PriceCurve,
+    pub rate: i128,
+    pub floor: i128, // per pixel price bounds, applied after the curve
+    pub cap: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintQuote {
+    pub hash: BytesN<32>,
+    pub shortfall: Vec<(Address, u32, u32)>, // (miner, color, amount) still to mine
+    pub cost: i128,                         // in the fee token at the current mining price
+    pub miners: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorAllowance {
+    pub amount: u32,
+    pub live_until_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphApproval {
+    pub approved: Address,
+    pub live_until_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphReservation {
+    pub reserver: Address,
+    pub live_until_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorSupply {
+    pub mined: u64,
+    pub burned: u64, // painted over on burning canvases
+    pub locked: u64, // held in glyphs (pending or minted) and on canvases
+    pub wallets: u64,
+}
+
+// What's stored per color, wallets are whatever is left of them
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColorCounters {
+    pub mined: u64,
+    pub burned: u64,
+    pub locked: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub glyphs_minted: u64,
+    pub glyphs_scraped: u64,
+    pub sales: u64, // matched offers, both asset sales and glyph swaps
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canvas {
+    pub owner: Address,
+    pub width: u32,
+    pub height: u32,
+    pub burn: bool, // burn painted over colors rather than return them to their painter
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CanvasPixel {
+    pub painter: Address,
+    pub miner: Address,
+    pub color: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub hash: BytesN<32>,
+    pub x: u32,
+    pub y: u32,
+    pub z: u32, // higher layers paint over lower ones, ties go to the later layer
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Map<u32, u32>, // index : color, unpainted pixels are left out
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    FlipH,
+    FlipV,
+    Rotate90, // clockwise
+    Rotate180,
+    Rotate270,
+    Invert,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GlyphStatus {
+    Unknown,
+    Building(u32), // pixels placed so far
+    Minted,
+    Scraping(u32, u32), // (color entries left, scrape calls left)
+    Scraped,
+    Animated,
+    Composite,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum OfferCreate {
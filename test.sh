@@ -42,7 +42,7 @@ echo 'glyph minted' $glyph_hash
 soroban contract invoke --id $contract_id --source default --network futurenet -- glyph_get --hash_type '{"Glyph": "'$glyph_hash'"}'
 
 # glyph_transfer
-soroban contract invoke --id $contract_id --source $user1_sk --network futurenet --fee 1000000000 -- glyph_transfer --to $user2_pk --hash_type '{"Glyph": "'$glyph_hash'"}'
+soroban contract invoke --id $contract_id --source $user1_sk --network futurenet --fee 1000000000 -- glyph_transfer --from $user1_pk --to $user2_pk --hash_type '{"Glyph": "'$glyph_hash'"}'
 
 # offer_post
 soroban contract invoke --id $contract_id --source $user2_sk --network futurenet -- offer_post --sell '{"Glyph": "'$glyph_hash'"}' --buy '{"Asset": ["'$token_address'", "100"]}'